@@ -0,0 +1,128 @@
+use jns42_generator::{generate_types, schema_url_from_arg, Error, Manager, MetaSchemaId};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::path::{Path, PathBuf};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Ident, LitInt, LitStr, Token};
+
+/// Expands to the types generated from a schema, the same types as the
+/// `package` command generates. The schema is a url or a path relative to the
+/// manifest of the crate. The generated code depends on `serde`, with the
+/// derive feature, and `serde_json`. An optional `unique_name_seed` changes
+/// the suffixes that make colliding type names unique.
+///
+/// ```
+/// jns42::import_schema!("../fixtures/array-with-ref.json");
+/// mod seeded {
+///     jns42::import_schema!("../fixtures/array-with-ref.json", unique_name_seed = 1);
+/// }
+///
+/// let value: ArrayWithRef = Vec::new();
+/// assert!(value.is_empty());
+/// ```
+#[proc_macro]
+pub fn import_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ImportSchemaInput {
+        location,
+        unique_name_seed,
+    } = parse_macro_input!(input as ImportSchemaInput);
+    let manifest_directory = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    match expand_import_schema(&location.value(), &manifest_directory, unique_name_seed) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => syn::Error::new(location.span(), error)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+// the location, optionally followed by `, unique_name_seed = <integer>`
+struct ImportSchemaInput {
+    location: LitStr,
+    unique_name_seed: usize,
+}
+
+impl Parse for ImportSchemaInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let location = input.parse()?;
+        let mut unique_name_seed = 0;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name: Ident = input.parse()?;
+            if name != "unique_name_seed" {
+                return Err(syn::Error::new(name.span(), "expected unique_name_seed"));
+            }
+            input.parse::<Token![=]>()?;
+            unique_name_seed = input.parse::<LitInt>()?.base10_parse()?;
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(Self {
+            location,
+            unique_name_seed,
+        })
+    }
+}
+
+fn expand_import_schema(
+    location: &str,
+    manifest_directory: &Path,
+    unique_name_seed: usize,
+) -> Result<TokenStream, Error> {
+    let schema_url = schema_url_from_arg(location, manifest_directory);
+
+    let mut manager = Manager::new();
+    manager.load_from_url(&schema_url, &schema_url, MetaSchemaId::Draft202012)?;
+
+    let mut tokens = quote! {};
+
+    // The compiler does not know what files a macro reads, including them
+    // makes it expand the macro again when one of them changes.
+    for fetched_url in manager.get_fetched_urls() {
+        if let Ok(path) = fetched_url.to_file_path() {
+            let path = path.to_string_lossy();
+            tokens.extend(quote! {
+                const _: &[u8] = include_bytes!(#path);
+            });
+        }
+    }
+
+    tokens.extend(generate_types(&manager, unique_name_seed)?);
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_array_with_ref() {
+        let manifest_directory = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        let tokens =
+            expand_import_schema("../fixtures/array-with-ref.json", manifest_directory, 0).unwrap();
+        let tokens = tokens.to_string();
+
+        assert!(tokens.contains("include_bytes !"));
+        assert!(tokens.contains("pub type ArrayWithRef = Vec < ArrayWithRefItems > ;"));
+
+        let error =
+            expand_import_schema("../fixtures/missing.json", manifest_directory, 0).unwrap_err();
+        assert!(error.to_string().starts_with("could not read file://"));
+    }
+
+    #[test]
+    fn parse_unique_name_seed() {
+        let input: ImportSchemaInput = syn::parse_quote!("schema.json", unique_name_seed = 3);
+        assert_eq!(input.location.value(), "schema.json");
+        assert_eq!(input.unique_name_seed, 3);
+
+        let input: ImportSchemaInput = syn::parse_quote!("schema.json");
+        assert_eq!(input.unique_name_seed, 0);
+
+        assert!(syn::parse_str::<ImportSchemaInput>(r#""schema.json", seed = 3"#).is_err());
+    }
+}
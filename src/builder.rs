@@ -0,0 +1,186 @@
+use crate::error::Error;
+use crate::generators::{
+    package::{format_file_token_stream, write_file},
+    types::generate_types,
+};
+use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Generates types from schemas in a build script.
+///
+/// The types are written to a single file in `OUT_DIR` that can be included
+/// in the crate. Every file that is read is reported to cargo via
+/// `cargo:rerun-if-changed`, so the types are regenerated when a schema
+/// changes. The generated code depends on `serde`, with the derive feature,
+/// and `serde_json`.
+///
+/// ```no_run
+/// // build.rs
+/// jns42_generator::Builder::new()
+///     .schema("schemas/x.json")
+///     .out_dir(std::env::var("OUT_DIR").unwrap())
+///     .generate()
+///     .unwrap();
+/// ```
+///
+/// ```ignore
+/// // src/lib.rs
+/// include!(concat!(env!("OUT_DIR"), "/types.rs"));
+/// ```
+pub struct Builder {
+    schema_locations: Vec<String>,
+    default_meta_schema_id: MetaSchemaId,
+    out_dir: Option<PathBuf>,
+    file_name: String,
+    unique_name_seed: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            schema_locations: Vec::new(),
+            default_meta_schema_id: MetaSchemaId::Draft202012,
+            out_dir: None,
+            file_name: "types.rs".to_owned(),
+            unique_name_seed: 0,
+        }
+    }
+
+    /// Adds a schema to generate types for, either a url or a path. Relative
+    /// paths are relative to the working directory, that is the directory of
+    /// the manifest in a build script.
+    pub fn schema(mut self, location: impl Into<String>) -> Self {
+        self.schema_locations.push(location.into());
+        self
+    }
+
+    /// The meta-schema of schemas without a `$schema`, draft 2020-12 if not
+    /// set.
+    pub fn default_meta_schema(mut self, meta_schema_id: MetaSchemaId) -> Self {
+        self.default_meta_schema_id = meta_schema_id;
+        self
+    }
+
+    /// The directory to write to, the `OUT_DIR` environment variable if not
+    /// set.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// The name of the generated file, types.rs if not set.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Changes the suffixes that make colliding type names unique, 0 if not
+    /// set.
+    pub fn unique_name_seed(mut self, unique_name_seed: usize) -> Self {
+        self.unique_name_seed = unique_name_seed;
+        self
+    }
+
+    /// Loads the schemas and writes the generated types, returns the path of
+    /// the generated file.
+    pub fn generate(self) -> Result<PathBuf, Error> {
+        let out_dir = match self.out_dir {
+            Some(out_dir) => out_dir,
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(Error::OutDirNotSet)?,
+        };
+
+        let mut manager = Manager::new();
+        let result = self.schema_locations.iter().try_for_each(|location| {
+            let schema_url = get_schema_url(location);
+            manager.load_from_url(&schema_url, &schema_url, self.default_meta_schema_id)
+        });
+
+        // also when loading failed, fixing a schema should trigger a new build
+        for fetched_url in manager.get_fetched_urls() {
+            if let Ok(path) = fetched_url.to_file_path() {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+        result?;
+
+        for warning in manager.get_unknown_keywords() {
+            println!("cargo:warning={}", warning);
+        }
+
+        let path = out_dir.join(self.file_name);
+        write_file(
+            &path,
+            format_file_token_stream(generate_types(&manager, self.unique_name_seed)?)?.as_str(),
+        )?;
+
+        Ok(path)
+    }
+}
+
+fn get_schema_url(location: &str) -> Url {
+    let base_directory =
+        std::env::current_dir().expect("the working directory should be accessible");
+    schema_url_from_arg(location, &base_directory)
+}
+
+/// The url of a schema given as an argument, either a url or a path relative
+/// to `base_directory`. Panics when `base_directory` is not absolute.
+pub fn schema_url_from_arg(location: &str, base_directory: &Path) -> Url {
+    // a single letter scheme is a windows drive letter, not a url
+    if let Ok(url) = Url::parse(location) {
+        if url.scheme().len() > 1 {
+            return url;
+        }
+    }
+
+    Url::from_file_path(base_directory.join(location))
+        .expect("the base directory should be an absolute path")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_directory::TestDirectory;
+    use std::fs;
+
+    #[test]
+    fn generate_includable_types() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/array-with-ref.json");
+        let test_directory = TestDirectory::new("generate_includable_types");
+        let out_dir = test_directory.path();
+
+        let path = Builder::new()
+            .schema(fixture_path.to_str().unwrap())
+            .out_dir(out_dir)
+            .generate()
+            .unwrap();
+
+        assert_eq!(path, out_dir.join("types.rs"));
+
+        let types = fs::read_to_string(path).unwrap();
+        assert!(!types.contains("//!"));
+        assert!(types.contains("pub type ArrayWithRef = Vec<ArrayWithRefItems>;"));
+    }
+
+    #[test]
+    fn get_schema_urls() {
+        assert_eq!(
+            get_schema_url("https://example.com/schema.json").as_str(),
+            "https://example.com/schema.json"
+        );
+        assert_eq!(
+            get_schema_url("schemas/x.json"),
+            Url::from_file_path(std::env::current_dir().unwrap().join("schemas/x.json")).unwrap()
+        );
+    }
+}
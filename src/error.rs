@@ -0,0 +1,267 @@
+use crate::utils::source_location::SourceLocation;
+use std::{fmt::Display, path::PathBuf};
+use url::Url;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    ReadFile {
+        url: Url,
+        source: std::io::Error,
+    },
+    Deserialize {
+        url: Url,
+        source: serde_json::Error,
+    },
+    #[cfg(feature = "http")]
+    Fetch {
+        url: Url,
+        source: Box<ureq::Error>,
+    },
+    #[cfg(feature = "http")]
+    UnexpectedContentType {
+        url: Url,
+        content_type: String,
+    },
+    UnsupportedScheme {
+        url: Url,
+    },
+    InvalidFilePath {
+        url: Url,
+    },
+    InvalidUrl {
+        base_url: Url,
+        reference: String,
+        source: url::ParseError,
+    },
+    InvalidReference {
+        node_url: Url,
+        reference: String,
+        source: url::ParseError,
+    },
+    InvalidRetrievalUrlMapping {
+        mapping: String,
+    },
+    InvalidRetrievalUrlMappings {
+        url: Url,
+    },
+    UnresolvedReference {
+        node_url: Url,
+        reference: String,
+    },
+    // not an error by itself, unknown keywords are allowed but they may be
+    // misspelled keywords
+    UnknownKeyword {
+        node_url: Url,
+        keyword: String,
+    },
+    RootNodeAlreadyPresent {
+        node_url: Url,
+    },
+    RootNodeNotFound {
+        node_url: Url,
+    },
+    DuplicateAnchor {
+        anchor_url: Url,
+    },
+    // the members of an allOf that no value can satisfy together, the
+    // message names the keyword and its conflicting values
+    ConflictingAllOf {
+        node_url: Url,
+        message: String,
+    },
+    SerializeExample {
+        node_url: Url,
+        source: serde_json::Error,
+    },
+    ParseGeneratedCode {
+        source: syn::Error,
+    },
+    WriteFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    OutDirNotSet,
+    // wraps an error about a node with the location of that node in its source
+    Located {
+        location: Box<SourceLocation>,
+        source: Box<Error>,
+    },
+    // wraps an error that happened while loading a referenced node, a chain
+    // of these is the chain of references that led to the error
+    Reference {
+        referrer_url: Url,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    // the node urls that lead to the error, outermost first
+    pub fn reference_chain(&self) -> Vec<&Url> {
+        let mut result = Vec::new();
+
+        let mut error = self;
+        while let Error::Reference {
+            referrer_url,
+            source,
+            ..
+        } = error
+        {
+            result.push(referrer_url);
+            error = source;
+        }
+
+        result
+    }
+
+    // the node the error is about, if any
+    pub fn node_url(&self) -> Option<&Url> {
+        match self {
+            Error::InvalidReference { node_url, .. } => Some(node_url),
+            Error::UnresolvedReference { node_url, .. } => Some(node_url),
+            Error::UnknownKeyword { node_url, .. } => Some(node_url),
+            Error::ConflictingAllOf { node_url, .. } => Some(node_url),
+            _ => None,
+        }
+    }
+
+    // the error without any reference context
+    pub fn root_error(&self) -> &Error {
+        match self {
+            Error::Reference { source, .. } => source.root_error(),
+            error => error,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ReadFile { url, .. } => write!(f, "could not read {}", url),
+            Error::Deserialize { url, source } => write!(
+                f,
+                "could not deserialize {} at line {} column {}",
+                url,
+                source.line(),
+                source.column()
+            ),
+            #[cfg(feature = "http")]
+            Error::Fetch { url, .. } => write!(f, "could not fetch {}", url),
+            #[cfg(feature = "http")]
+            Error::UnexpectedContentType { url, content_type } => {
+                write!(f, "unexpected content type {} for {}", content_type, url)
+            }
+            Error::UnsupportedScheme { url } => {
+                write!(f, "the scheme of {} is not supported", url)
+            }
+            Error::InvalidFilePath { url } => {
+                write!(f, "{} is not a valid file path", url)
+            }
+            Error::InvalidUrl {
+                base_url,
+                reference,
+                ..
+            } => write!(f, "could not resolve {} against {}", reference, base_url),
+            Error::InvalidReference {
+                node_url,
+                reference,
+                ..
+            } => write!(f, "invalid reference {} in {}", reference, node_url),
+            Error::InvalidRetrievalUrlMapping { mapping } => write!(
+                f,
+                "expected a mapping like <url-prefix>=<mapped-url-prefix>, got {}",
+                mapping
+            ),
+            Error::InvalidRetrievalUrlMappings { url } => write!(
+                f,
+                "expected an object of url prefixes and mapped url prefixes in {}",
+                url
+            ),
+            Error::UnresolvedReference {
+                node_url,
+                reference,
+            } => write!(f, "unresolved reference {} in {}", reference, node_url),
+            Error::UnknownKeyword { node_url, keyword } => {
+                write!(f, "unknown keyword {} in {}", keyword, node_url)
+            }
+            Error::RootNodeAlreadyPresent { node_url } => {
+                write!(f, "{} is already loaded", node_url)
+            }
+            Error::RootNodeNotFound { node_url } => write!(f, "{} is not loaded", node_url),
+            Error::DuplicateAnchor { anchor_url } => write!(f, "duplicate anchor {}", anchor_url),
+            Error::ConflictingAllOf { node_url, message } => {
+                write!(f, "conflicting allOf members in {}: {}", node_url, message)
+            }
+            Error::SerializeExample { node_url, .. } => {
+                write!(f, "could not serialize example of {}", node_url)
+            }
+            Error::ParseGeneratedCode { .. } => write!(f, "could not parse generated code"),
+            Error::WriteFile { path, .. } => write!(f, "could not write {}", path.display()),
+            Error::OutDirNotSet => write!(
+                f,
+                "no out dir is set and the OUT_DIR environment variable is not set"
+            ),
+            Error::Located { location, source } => write!(f, "{}: {}", location, source),
+            Error::Reference { referrer_url, .. } => {
+                write!(f, "error in a reference from {}", referrer_url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ReadFile { source, .. } => Some(source),
+            Error::Deserialize { source, .. } => Some(source),
+            #[cfg(feature = "http")]
+            Error::Fetch { source, .. } => Some(source.as_ref()),
+            Error::InvalidUrl { source, .. } => Some(source),
+            Error::InvalidReference { source, .. } => Some(source),
+            // the source is part of the message
+            Error::Located { source, .. } => source.source(),
+            Error::SerializeExample { source, .. } => Some(source),
+            Error::ParseGeneratedCode { source } => Some(source),
+            Error::WriteFile { source, .. } => Some(source),
+            Error::Reference { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+// joins reference onto base_url, like Url::join, but with a useful error
+pub fn join_url(base_url: &Url, reference: &str) -> Result<Url, Error> {
+    base_url
+        .join(reference)
+        .map_err(|source| Error::InvalidUrl {
+            base_url: base_url.clone(),
+            reference: reference.to_owned(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_chain() {
+        let a: Url = "https://example.com/a".parse().unwrap();
+        let b: Url = "https://example.com/b".parse().unwrap();
+        let c: Url = "https://example.com/c".parse().unwrap();
+
+        let error = Error::Reference {
+            referrer_url: a.clone(),
+            source: Box::new(Error::Reference {
+                referrer_url: b.clone(),
+                source: Box::new(Error::UnsupportedScheme { url: c.clone() }),
+            }),
+        };
+
+        assert_eq!(error.reference_chain(), vec![&a, &b]);
+        assert_eq!(
+            error.root_error().to_string(),
+            "the scheme of https://example.com/c is not supported"
+        );
+    }
+}
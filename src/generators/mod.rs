@@ -0,0 +1,4 @@
+pub mod names;
+pub mod package;
+pub mod test_cases;
+pub mod types;
@@ -0,0 +1,439 @@
+use crate::schemas::intermediate::IntermediateNode;
+use crate::utils::json_pointer::{json_pointer_from_fragment, parse_json_pointer};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use url::Url;
+
+// keywords that hold named sub schemas, the name alone is enough
+const CONTAINER_KEYWORDS: [&str; 6] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "dependencies",
+];
+
+// strict and reserved keywords, in lowercase because names are snake case
+const KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// type names that would shadow a type the generated code refers to
+const RESERVED_TYPE_NAMES: [&str; 4] = ["Option", "String", "Vec", "Box"];
+
+/// A unique type name for every node.
+///
+/// Every node has a list of candidate names, from the title or the shortest
+/// name derived from its url to names with more and more context. Nodes whose
+/// names collide move to their next candidate, so the name of a node only
+/// changes when another node gets the same name. When there are no candidates
+/// left a suffix derived from the url and the seed makes the name unique.
+pub fn get_type_names(
+    nodes: &IndexMap<Url, IntermediateNode>,
+    unique_name_seed: usize,
+) -> HashMap<Url, String> {
+    let candidates: Vec<_> = nodes
+        .values()
+        .map(|node| (&node.node_url, get_candidate_names(node)))
+        .collect();
+    let mut levels = vec![0; candidates.len()];
+
+    loop {
+        let names: Vec<_> = candidates
+            .iter()
+            .zip(&levels)
+            .map(|((node_url, candidates), level)| {
+                get_candidate_name(node_url, candidates, *level, unique_name_seed)
+            })
+            .collect();
+
+        let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, name) in names.iter().enumerate() {
+            groups.entry(name.as_str()).or_default().push(index);
+        }
+
+        let mut moves: Vec<usize> = Vec::new();
+        for group in groups.values().filter(|group| group.len() > 1) {
+            // a node that got the name with fewer tries keeps it
+            let min_level = group.iter().map(|index| levels[*index]).min().unwrap();
+            let min_level_count = group
+                .iter()
+                .filter(|index| levels[**index] == min_level)
+                .count();
+
+            moves.extend(
+                group
+                    .iter()
+                    .filter(|index| levels[**index] > min_level || min_level_count > 1),
+            );
+        }
+
+        if moves.is_empty() {
+            return candidates
+                .into_iter()
+                .map(|(node_url, _candidates)| node_url.clone())
+                .zip(names)
+                .collect();
+        }
+
+        for index in moves {
+            levels[index] += 1;
+        }
+    }
+}
+
+/// A type name from a text like a title, with every character that is not
+/// valid in an identifier removed. Letters that are not ascii are kept, like
+/// in Größe. Names of the types that the generated code uses without a path,
+/// like Option, get a suffix.
+pub fn to_type_name(parts: &[&str]) -> String {
+    let mut result = to_variant_name(parts);
+
+    if RESERVED_TYPE_NAMES.contains(&result.as_str()) {
+        result.push_str("Type");
+    }
+
+    result
+}
+
+/// A name like a type name, for an enum variant. A variant does not shadow a
+/// type, so it may be called String.
+pub fn to_variant_name(parts: &[&str]) -> String {
+    let mut result: String = parts
+        .iter()
+        .flat_map(|part| split_words(part))
+        .map(|word| {
+            let mut characters = word.chars();
+            let first = characters.next().unwrap_or_default();
+            format!("{}{}", first.to_uppercase(), characters.as_str())
+        })
+        .collect();
+
+    if result.is_empty() {
+        result = "Type".to_owned();
+    }
+    if result.starts_with(|character: char| !character.is_alphabetic()) {
+        result = format!("T{}", result);
+    }
+    // the only keyword that is a valid type name
+    if result == "Self" {
+        result = "SelfType".to_owned();
+    }
+
+    result
+}
+
+/// A field or function name from a text like a property name. Keywords get
+/// an underscore appended.
+pub fn to_member_name(text: &str) -> String {
+    let mut result = to_snake_case(&to_type_name(&[text]));
+
+    if KEYWORDS.contains(&result.as_str()) {
+        result.push('_');
+    }
+
+    result
+}
+
+pub fn to_snake_case(name: &str) -> String {
+    let mut result = String::new();
+
+    for (index, character) in name.chars().enumerate() {
+        if character.is_uppercase() {
+            if index > 0 {
+                result.push('_');
+            }
+            result.extend(character.to_lowercase());
+        } else {
+            result.push(character);
+        }
+    }
+
+    result
+}
+
+// letters and digits, also the ones that are not ascii, identifiers may have
+// them
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for character in text.chars() {
+        if character.is_alphanumeric() {
+            word.push(character);
+            continue;
+        }
+
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+fn get_candidate_name(
+    node_url: &Url,
+    candidates: &[String],
+    level: usize,
+    unique_name_seed: usize,
+) -> String {
+    if let Some(name) = candidates.get(level) {
+        return name.clone();
+    }
+
+    let attempt = level - candidates.len();
+    let hash = hash_name(&[
+        unique_name_seed.to_string().as_str(),
+        node_url.as_str(),
+        attempt.to_string().as_str(),
+    ]);
+
+    format!(
+        "{}{:06x}",
+        candidates.last().map(String::as_str).unwrap_or("Type"),
+        hash & 0xffffff
+    )
+}
+
+// From the title and the shortest name to the name with all context. The url
+// is the document, by its path segments, followed by the pointer to the node.
+fn get_candidate_names(node: &IntermediateNode) -> Vec<String> {
+    let node_url = &node.node_url;
+
+    let mut document_parts: Vec<String> = match node_url.path_segments() {
+        Some(segments) => segments
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.to_owned())
+            .collect(),
+        None => vec![node_url.path().to_owned()],
+    };
+    if let Some(last) = document_parts.last_mut() {
+        // the extension of a file, like schema.json
+        if let Some((stem, _extension)) = last.rsplit_once('.') {
+            if !stem.is_empty() {
+                *last = stem.to_owned();
+            }
+        }
+    }
+    if let Some(host) = node_url.host_str() {
+        document_parts.insert(0, host.to_owned());
+    }
+
+    let pointer_parts = match node_url.fragment() {
+        None | Some("") => Vec::new(),
+        Some(fragment) => {
+            let pointer = json_pointer_from_fragment(fragment);
+            // anchors are not pointers, they are a name by themselves
+            parse_json_pointer(pointer.as_str()).unwrap_or_else(|| vec![pointer])
+        }
+    };
+    let short_pointer_parts: Vec<_> = pointer_parts
+        .iter()
+        .enumerate()
+        .filter(|(index, part)| {
+            let is_container = CONTAINER_KEYWORDS.contains(&part.as_str())
+                && index + 1 < pointer_parts.len()
+                && !is_container_name(&pointer_parts, *index);
+            !is_container
+        })
+        .map(|(_index, part)| part.as_str())
+        .collect();
+    let pointer_parts: Vec<_> = pointer_parts.iter().map(String::as_str).collect();
+
+    let mut result = Vec::new();
+
+    if let Some(title) = &node.title {
+        result.push(to_type_name(&[title]));
+    }
+
+    // the last segment that is a name, not a version like 2022-10-07
+    let document_parts: Vec<_> = document_parts.iter().map(String::as_str).collect();
+    let name_index = document_parts
+        .iter()
+        .rposition(|part| {
+            part.chars()
+                .any(|character| character.is_ascii_alphabetic())
+        })
+        .unwrap_or(document_parts.len().saturating_sub(1));
+    let name_document_parts = document_parts
+        .get(name_index..name_index + 1)
+        .unwrap_or_default();
+
+    result.push(to_type_name(
+        &[name_document_parts, &short_pointer_parts].concat(),
+    ));
+    result.push(to_type_name(
+        &[name_document_parts, &pointer_parts].concat(),
+    ));
+    for start in (0..=name_index).rev() {
+        result.push(to_type_name(
+            &[&document_parts[start..], &pointer_parts[..]].concat(),
+        ));
+    }
+
+    result.dedup();
+    result
+}
+
+// whether the part at index is the name of a sub schema in a container, like
+// a property named properties
+fn is_container_name(parts: &[String], index: usize) -> bool {
+    index > 0
+        && CONTAINER_KEYWORDS.contains(&parts[index - 1].as_str())
+        && !is_container_name(parts, index - 1)
+}
+
+// fnv-1a, the hashers of std may change between versions of rust
+fn hash_name(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in parts.iter().flat_map(|part| part.bytes().chain([0])) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+    use crate::utils::value_rc::ValueRc;
+    use std::rc::Rc;
+
+    fn get_names(source: &str, unique_name_seed: usize) -> Vec<(String, String)> {
+        let node: ValueRc = serde_json::from_str(source).unwrap();
+        let node_url: Url = "https://jns42-generator.org/simple-object".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let names = get_type_names(&manager.get_intermediate_nodes().unwrap(), unique_name_seed);
+        let mut names: Vec<_> = names
+            .into_iter()
+            .map(|(node_url, name)| (node_url.fragment().unwrap_or_default().to_owned(), name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn pairs(names: &[(&str, &str)]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|(fragment, name)| (fragment.to_string(), name.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn type_names() {
+        assert_eq!(
+            get_names(
+                r#"
+                {
+                    "properties": {
+                        "name": { "type": "string" },
+                        "properties": { "title": "Größe" },
+                        "self": { "title": "self" }
+                    },
+                    "$defs": {
+                        "name": { "type": "string" },
+                        "items": { "items": true }
+                    }
+                }
+                "#,
+                0
+            ),
+            pairs(&[
+                ("", "SimpleObject"),
+                ("/$defs/items", "SimpleObjectItems"),
+                ("/$defs/items/items", "SimpleObjectItemsItems"),
+                ("/$defs/name", "SimpleObjectDefsName"),
+                ("/properties/name", "SimpleObjectPropertiesName"),
+                ("/properties/properties", "Größe"),
+                ("/properties/self", "SelfType"),
+            ])
+        );
+
+        // an unrelated node does not change the name of another
+        assert_eq!(
+            get_names(r#"{ "properties": { "name": true } }"#, 0),
+            pairs(&[
+                ("", "SimpleObject"),
+                ("/properties/name", "SimpleObjectName")
+            ])
+        );
+    }
+
+    #[test]
+    fn collisions() {
+        let names = get_names(
+            r#"
+            {
+                "$defs": {
+                    "a": { "title": "Same" },
+                    "b": { "title": "Same" }
+                },
+                "properties": {
+                    "a": { "title": "Same" }
+                }
+            }
+            "#,
+            0,
+        );
+        assert_eq!(names[1].1, "SimpleObjectDefsA");
+        assert_eq!(names[2].1, "SimpleObjectB");
+        assert_eq!(names[3].1, "SimpleObjectPropertiesA");
+
+        assert_eq!(to_type_name(&["1 a-b c"]), "T1ABC");
+        assert_eq!(to_type_name(&["option"]), "OptionType");
+        assert_eq!(to_type_name(&["String"]), "StringType");
+        assert_eq!(to_variant_name(&["String"]), "String");
+        assert_eq!(to_type_name(&["äpfel birnen"]), "ÄpfelBirnen");
+        assert_eq!(to_member_name("ÄpfelBirnen"), "äpfel_birnen");
+        assert_eq!(to_snake_case("SimpleObject"), "simple_object");
+        assert_eq!(to_member_name("type"), "type_");
+        assert_eq!(to_member_name("$ref"), "ref_");
+        assert_eq!(to_member_name("x-name"), "x_name");
+    }
+
+    #[test]
+    fn unique_name_seed() {
+        // the same names on every level, only the seed tells them apart
+        let mut manager = Manager::new();
+        for node_url in ["https://example.com/x", "http://example.com/x"] {
+            manager
+                .load_root_node(
+                    Rc::new(ValueRc::Bool(true)),
+                    &node_url.parse().unwrap(),
+                    MetaSchemaId::Draft202012,
+                )
+                .unwrap();
+        }
+        let nodes = manager.get_intermediate_nodes().unwrap();
+
+        let names = get_type_names(&nodes, 0);
+        let mut values: Vec<_> = names.values().collect();
+        values.sort();
+        values.dedup();
+        assert_eq!(values.len(), 2);
+        assert!(values
+            .iter()
+            .all(|name| name.starts_with("ExampleComX") && name.len() == 17));
+
+        assert_eq!(names, get_type_names(&nodes, 0));
+        assert_ne!(names, get_type_names(&nodes, 1));
+    }
+}
@@ -0,0 +1,154 @@
+use super::{names::get_type_names, test_cases::TestCasesGenerator, types::TypesGenerator};
+use crate::error::Error;
+use crate::schemas::manager::Manager;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::{fs, path::Path};
+
+/// Options for generate_package.
+pub struct PackageOptions<'p> {
+    pub package_name: &'p str,
+    pub package_version: &'p str,
+    pub generate_test: bool,
+    /// Changes the suffixes that make colliding type names unique.
+    pub unique_name_seed: usize,
+}
+
+/// Writes a crate with a type for every node loaded in the manager to
+/// package_directory.
+pub fn generate_package(
+    manager: &Manager,
+    package_directory: &Path,
+    options: &PackageOptions,
+) -> Result<(), Error> {
+    let nodes = manager.get_merged_intermediate_nodes()?;
+    let names = get_type_names(&nodes, options.unique_name_seed);
+
+    let source_directory = package_directory.join("src");
+    fs::create_dir_all(&source_directory).map_err(|source| Error::WriteFile {
+        path: source_directory.clone(),
+        source,
+    })?;
+
+    write_file(
+        &package_directory.join("Cargo.toml"),
+        generate_manifest(options).as_str(),
+    )?;
+
+    write_file(
+        &source_directory.join("lib.rs"),
+        format_file_token_stream(generate_lib_token_stream(options))?.as_str(),
+    )?;
+
+    let mut types_generator = TypesGenerator::new(&nodes, &names);
+    write_file(
+        &source_directory.join("types.rs"),
+        format_file_token_stream(types_generator.generate_file_token_stream())?.as_str(),
+    )?;
+
+    if options.generate_test {
+        let test_cases_generator = TestCasesGenerator::new(&nodes, &names);
+        write_file(
+            &source_directory.join("tests.rs"),
+            format_file_token_stream(test_cases_generator.generate_file_token_stream()?)?.as_str(),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn generate_manifest(options: &PackageOptions) -> String {
+    let PackageOptions {
+        package_name,
+        package_version,
+        ..
+    } = options;
+
+    format!(
+        r#"[package]
+name = "{package_name}"
+version = "{package_version}"
+edition = "2021"
+
+[dependencies]
+serde = {{ version = "1", features = ["derive"] }}
+serde_json = "1"
+"#
+    )
+}
+
+fn generate_lib_token_stream(options: &PackageOptions) -> TokenStream {
+    let mut tokens = quote! {
+        //! This file is generated, do not edit it manually.
+
+        mod types;
+
+        pub use types::*;
+    };
+
+    if options.generate_test {
+        tokens.extend(quote! {
+            #[cfg(test)]
+            mod tests;
+        });
+    }
+
+    tokens
+}
+
+pub(crate) fn format_file_token_stream(tokens: TokenStream) -> Result<String, Error> {
+    let file = syn::parse2(tokens).map_err(|source| Error::ParseGeneratedCode { source })?;
+
+    Ok(prettyplease::unparse(&file))
+}
+
+pub(crate) fn write_file(path: &Path, content: &str) -> Result<(), Error> {
+    fs::write(path, content).map_err(|source| Error::WriteFile {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::meta::MetaSchemaId;
+    use crate::utils::test_directory::TestDirectory;
+    use url::Url;
+
+    #[test]
+    fn generate_array_with_ref_package() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/array-with-ref.json");
+        let schema_url = Url::from_file_path(fixture_path).unwrap();
+        let test_directory = TestDirectory::new("generate_array_with_ref_package");
+        let package_directory = test_directory.path();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&schema_url, &schema_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        generate_package(
+            &manager,
+            package_directory,
+            &PackageOptions {
+                package_name: "array-with-ref",
+                package_version: "0.0.0",
+                generate_test: false,
+                unique_name_seed: 0,
+            },
+        )
+        .unwrap();
+
+        let types = fs::read_to_string(package_directory.join("src/types.rs")).unwrap();
+        assert!(types.contains("pub type ArrayWithRef = Vec<ArrayWithRefItems>;"));
+        assert!(types.contains("pub type SimpleObjectId = i64;"));
+        assert!(types.contains("pub struct SimpleObject {"));
+        assert!(types.contains("pub id: i64,"));
+        assert!(types.contains("pub name: Option<String>,"));
+
+        let manifest = fs::read_to_string(package_directory.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains(r#"serde = { version = "1", features = ["derive"] }"#));
+    }
+}
@@ -0,0 +1,61 @@
+use super::names::to_snake_case;
+use crate::error::Error;
+use crate::schemas::intermediate::IntermediateNode;
+use indexmap::IndexMap;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use url::Url;
+
+pub struct TestCasesGenerator<'b> {
+    nodes: &'b IndexMap<Url, IntermediateNode>,
+    names: &'b HashMap<Url, String>,
+}
+
+impl<'b> TestCasesGenerator<'b> {
+    pub fn new(
+        nodes: &'b IndexMap<Url, IntermediateNode>,
+        names: &'b HashMap<Url, String>,
+    ) -> Self {
+        Self { nodes, names }
+    }
+
+    pub fn generate_file_token_stream(&self) -> Result<TokenStream, Error> {
+        let mut tokens = quote! {
+            //! This file is generated, do not edit it manually.
+        };
+
+        let mut node_urls: Vec<_> = self.names.keys().collect();
+        node_urls.sort_by(|left, right| left.as_str().cmp(right.as_str()));
+
+        for node_url in node_urls {
+            let name = self.names.get(node_url).unwrap();
+            let type_identifier = format_ident!("{}", name);
+
+            let examples = self
+                .nodes
+                .get(node_url)
+                .map(|node| node.examples.as_slice())
+                .unwrap_or_default();
+            for (index, example) in examples.iter().enumerate() {
+                let test_identifier =
+                    format_ident!("{}_example_{}", to_snake_case(name), index + 1);
+                let example = serde_json::to_string(example.as_ref()).map_err(|source| {
+                    Error::SerializeExample {
+                        node_url: node_url.clone(),
+                        source,
+                    }
+                })?;
+
+                tokens.extend(quote! {
+                    #[test]
+                    fn #test_identifier() {
+                        let _value: super::#type_identifier = serde_json::from_str(#example).unwrap();
+                    }
+                });
+            }
+        }
+
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,1283 @@
+use super::names::{get_type_names, to_member_name, to_variant_name};
+use crate::error::Error;
+use crate::schemas::{
+    intermediate::{IntermediateNode, IntermediateType},
+    manager::Manager,
+};
+use indexmap::IndexMap;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use url::Url;
+
+/// The types of every node loaded in the manager, the same types as in a
+/// generated package but without inner attributes so they can be included
+/// anywhere. The seed changes the suffixes that make colliding type names
+/// unique, like in [`PackageOptions`]. Fails when the members of an allOf
+/// conflict.
+///
+/// [`PackageOptions`]: super::package::PackageOptions
+pub fn generate_types(manager: &Manager, unique_name_seed: usize) -> Result<TokenStream, Error> {
+    let nodes = manager.get_merged_intermediate_nodes()?;
+    let names = get_type_names(&nodes, unique_name_seed);
+
+    Ok(TypesGenerator::new(&nodes, &names).generate_types_token_stream())
+}
+
+enum TypeState {
+    Generating,
+    Generated(TokenStream),
+}
+
+pub struct TypesGenerator<'b> {
+    nodes: &'b IndexMap<Url, IntermediateNode>,
+    names: &'b HashMap<Url, String>,
+    states: HashMap<Url, TypeState>,
+    // the items of the nodes that are not a type alias, like structs
+    definitions: HashMap<Url, TokenStream>,
+    // the members of tagged unions that are the payload of their variant,
+    // with the tag, their struct does not have the tag as a field
+    tagged_members: HashMap<Url, String>,
+    // the nodes that are being generated, outermost first, with the number of
+    // indirections when they started
+    generating: Vec<(Url, usize)>,
+    // the number of types being generated that store their contents on the
+    // heap, like a Vec, a type in them may contain the type around it
+    indirections: usize,
+}
+
+impl<'b> TypesGenerator<'b> {
+    pub fn new(
+        nodes: &'b IndexMap<Url, IntermediateNode>,
+        names: &'b HashMap<Url, String>,
+    ) -> Self {
+        let mut generator = Self {
+            nodes,
+            names,
+            states: HashMap::new(),
+            definitions: HashMap::new(),
+            tagged_members: HashMap::new(),
+            generating: Vec::new(),
+            indirections: 0,
+        };
+
+        // before any struct is generated, a member may be generated before
+        // its union
+        for node in nodes.values() {
+            let Some(members) = generator.select_union_members(node) else {
+                continue;
+            };
+            let resolved_members: Vec<_> = members
+                .iter()
+                .map(|(_member, resolved)| *resolved)
+                .collect();
+            let Some((tag, _values)) = generator.select_union_tag(&resolved_members) else {
+                continue;
+            };
+
+            for (member, _resolved) in members {
+                if generator.is_tagged_member_struct(member) {
+                    generator
+                        .tagged_members
+                        .insert(member.node_url.clone(), tag.clone());
+                }
+            }
+        }
+
+        generator
+    }
+
+    pub fn generate_file_token_stream(&mut self) -> TokenStream {
+        let mut tokens = quote! {
+            //! This file is generated, do not edit it manually.
+        };
+
+        tokens.extend(self.generate_types_token_stream());
+
+        tokens
+    }
+
+    // without inner attributes, so the result can be included in another file
+    pub fn generate_types_token_stream(&mut self) -> TokenStream {
+        let mut tokens = quote! {};
+
+        let mut node_urls: Vec<_> = self.names.keys().cloned().collect();
+        node_urls.sort_by(|left, right| left.as_str().cmp(right.as_str()));
+
+        for node_url in node_urls {
+            tokens.extend(self.generate_type_token_stream(&node_url));
+        }
+
+        tokens
+    }
+
+    fn generate_type_token_stream(&mut self, node_url: &Url) -> TokenStream {
+        let type_identifier = self.get_type_identifier(node_url);
+        let type_expression = self.get_type_expression(node_url);
+
+        let mut tokens = quote! {};
+
+        let nodes = self.nodes;
+        if let Some(description) = nodes
+            .get(node_url)
+            .and_then(|node| node.description.as_ref())
+        {
+            let description = format!(" {}", description);
+            tokens.extend(quote! {
+                #[doc = #description]
+            });
+        }
+
+        match self.definitions.get(node_url) {
+            Some(definition) => tokens.extend(definition.clone()),
+            None => tokens.extend(quote! {
+                pub type #type_identifier = #type_expression;
+            }),
+        }
+
+        tokens
+    }
+
+    fn get_type_identifier(&self, node_url: &Url) -> TokenStream {
+        let name = self.names.get(node_url).unwrap();
+        let identifier = format_ident!("{}", name);
+
+        quote! { #identifier }
+    }
+
+    // A reference back to a type that is still being generated is recursive.
+    // A struct or enum may refer to itself, but it can only contain itself
+    // through a pointer, so without a Vec or map in between it is boxed. Type
+    // aliases may not be recursive at all, a cycle of only aliases falls back
+    // to a json value. Primitive types are used as they are instead of by
+    // their alias.
+    fn get_type_reference(&mut self, node_url: &Url) -> TokenStream {
+        if !self.names.contains_key(node_url) {
+            return quote! { serde_json::Value };
+        }
+
+        let type_expression = match self.states.get(node_url) {
+            Some(TypeState::Generating) => return self.get_recursive_type_reference(node_url),
+            Some(TypeState::Generated(tokens)) => tokens.clone(),
+            None => self.get_type_expression(node_url),
+        };
+
+        let is_primitive = self.nodes.get(node_url).is_some_and(|node| {
+            node.select_reference().is_none()
+                && node.select_single_type().is_some_and(|value| {
+                    !matches!(value, IntermediateType::Array | IntermediateType::Object)
+                })
+        });
+        if is_primitive {
+            return type_expression;
+        }
+
+        self.get_type_identifier(node_url)
+    }
+
+    fn get_recursive_type_reference(&self, node_url: &Url) -> TokenStream {
+        let Some(start) = self
+            .generating
+            .iter()
+            .position(|(generating_url, _indirections)| generating_url == node_url)
+        else {
+            return quote! { serde_json::Value };
+        };
+
+        let cycle = &self.generating[start..];
+        if !cycle
+            .iter()
+            .any(|(generating_url, _indirections)| self.is_definition(generating_url))
+        {
+            return quote! { serde_json::Value };
+        }
+
+        let type_identifier = self.get_type_identifier(node_url);
+        if cycle[0].1 == self.indirections {
+            return quote! { Box<#type_identifier> };
+        }
+        type_identifier
+    }
+
+    fn get_type_expression(&mut self, node_url: &Url) -> TokenStream {
+        if let Some(TypeState::Generated(tokens)) = self.states.get(node_url) {
+            return tokens.clone();
+        }
+
+        self.states.insert(node_url.clone(), TypeState::Generating);
+        self.generating.push((node_url.clone(), self.indirections));
+        let tokens = self.generate_type_expression(node_url);
+        self.generating.pop();
+        self.states
+            .insert(node_url.clone(), TypeState::Generated(tokens.clone()));
+
+        tokens
+    }
+
+    fn generate_type_expression(&mut self, node_url: &Url) -> TokenStream {
+        let nodes = self.nodes;
+        let Some(node) = nodes.get(node_url) else {
+            return quote! { serde_json::Value };
+        };
+
+        if let Some(node_ref_url) = node.select_reference() {
+            return self.get_type_reference(node_ref_url);
+        }
+
+        if let Some(tokens) = self.generate_union_type_expression(node) {
+            return tokens;
+        }
+
+        if let Some(types) = node.types.as_deref().filter(|types| types.len() > 1) {
+            return self.generate_types_enum_expression(node, types);
+        }
+
+        match node.select_single_type() {
+            Some(IntermediateType::Null) => quote! { () },
+            Some(IntermediateType::Boolean) => quote! { bool },
+            Some(IntermediateType::Integer) => quote! { i64 },
+            Some(IntermediateType::Number) => quote! { f64 },
+            Some(IntermediateType::String) => quote! { String },
+            Some(IntermediateType::Array) => {
+                let item_tokens = self.get_items_type(node);
+                quote! { Vec<#item_tokens> }
+            }
+            Some(IntermediateType::Object) => self.generate_object_type_expression(node),
+            None if !node.properties.is_empty() => self.generate_object_type_expression(node),
+            None => quote! { serde_json::Value },
+        }
+    }
+
+    // Whether the type of a node is a struct or an enum, not a type alias.
+    fn is_definition(&self, node_url: &Url) -> bool {
+        let Some(node) = self.nodes.get(node_url) else {
+            return false;
+        };
+
+        if node.select_reference().is_some() {
+            return false;
+        }
+        if self.select_union_members(node).is_some() {
+            return true;
+        }
+        if node.types.as_ref().is_some_and(|types| types.len() > 1) {
+            return true;
+        }
+
+        match node.select_single_type() {
+            Some(IntermediateType::Object) => self.is_struct(node),
+            None => !node.properties.is_empty() && self.is_struct(node),
+            _ => false,
+        }
+    }
+
+    // The members of oneOf or anyOf, with the nodes they resolve to, if every
+    // member has a type.
+    fn select_union_members(
+        &self,
+        node: &IntermediateNode,
+    ) -> Option<Vec<(&'b IntermediateNode, &'b IntermediateNode)>> {
+        let member_urls = if node.one_of.is_empty() {
+            &node.any_of
+        } else {
+            &node.one_of
+        };
+        // the properties of the node itself, like those of a merged allOf,
+        // would be lost in the variants
+        if member_urls.is_empty() || !node.properties.is_empty() {
+            return None;
+        }
+
+        let nodes = self.nodes;
+        let members: Vec<_> = member_urls
+            .iter()
+            .map(|member_url| {
+                let member = nodes.get(member_url)?;
+                let resolved = self.resolve_node(member_url)?;
+                Some((member, resolved))
+            })
+            .collect::<Option<_>>()?;
+        // members that are unions themselves are enums too
+        if members.iter().any(|(_member, resolved)| {
+            resolved.types.is_none()
+                && resolved.properties.is_empty()
+                && resolved.one_of.is_empty()
+                && resolved.any_of.is_empty()
+        }) {
+            return None;
+        }
+
+        Some(members)
+    }
+
+    // An enum with a variant for every member of oneOf or anyOf, if every
+    // member has a type. Members that share a property with a different
+    // constant value for every member are told apart by that property, the
+    // others by trying them in order.
+    fn generate_union_type_expression(&mut self, node: &IntermediateNode) -> Option<TokenStream> {
+        let members = self.select_union_members(node)?;
+
+        let type_identifier = self.get_type_identifier(&node.node_url);
+        let resolved_members: Vec<_> = members
+            .iter()
+            .map(|(_member, resolved)| *resolved)
+            .collect();
+
+        let mut tokens = quote! {
+            #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        };
+
+        if let Some((tag, values)) = self.select_union_tag(&resolved_members) {
+            let mut variants_tokens = quote! {};
+            let mut variant_names = Vec::new();
+
+            for ((member, resolved), value) in members.iter().zip(values) {
+                let variant_name =
+                    get_unique_variant_name(&mut variant_names, to_variant_name(&[&value]));
+                let variant_identifier = format_ident!("{}", variant_name);
+
+                variants_tokens.extend(self.generate_variant_doc_token_stream(member));
+                variants_tokens.extend(quote! {
+                    #[serde(rename = #value)]
+                });
+
+                // serde does not pass the tag on to the payload, a referenced
+                // struct that has it as a field is inlined instead
+                if self.tagged_members.contains_key(&member.node_url) {
+                    let payload_tokens = self.get_type_expression(&member.node_url);
+                    variants_tokens.extend(quote! {
+                        #variant_identifier(#payload_tokens),
+                    });
+                } else {
+                    let fields_tokens =
+                        self.generate_fields_token_stream(resolved, Some(&tag), quote! {});
+                    variants_tokens.extend(quote! {
+                        #variant_identifier {
+                            #fields_tokens
+                        },
+                    });
+                }
+            }
+
+            tokens.extend(quote! {
+                #[serde(tag = #tag)]
+                pub enum #type_identifier {
+                    #variants_tokens
+                }
+            });
+        } else {
+            let names: Vec<_> = members
+                .iter()
+                .enumerate()
+                .map(|(index, (member, _resolved))| self.get_variant_name(member, index))
+                .collect();
+            let mut variants_tokens = quote! {};
+            let mut variant_names = Vec::new();
+
+            for ((member, _resolved), name) in members.iter().zip(strip_common_prefix(names)) {
+                let variant_name = get_unique_variant_name(&mut variant_names, name);
+                let variant_identifier = format_ident!("{}", variant_name);
+
+                variants_tokens.extend(self.generate_variant_doc_token_stream(member));
+                if member.select_reference().is_none()
+                    && member.select_single_type() == Some(IntermediateType::Null)
+                {
+                    variants_tokens.extend(quote! {
+                        #variant_identifier,
+                    });
+                } else {
+                    let payload_tokens = self
+                        .get_type_reference(member.select_reference().unwrap_or(&member.node_url));
+                    variants_tokens.extend(quote! {
+                        #variant_identifier(#payload_tokens),
+                    });
+                }
+            }
+
+            tokens.extend(quote! {
+                #[serde(untagged)]
+                pub enum #type_identifier {
+                    #variants_tokens
+                }
+            });
+        }
+
+        self.definitions.insert(node.node_url.clone(), tokens);
+
+        Some(type_identifier)
+    }
+
+    // An enum with a variant for every type, like for a string or a boolean.
+    fn generate_types_enum_expression(
+        &mut self,
+        node: &IntermediateNode,
+        types: &[IntermediateType],
+    ) -> TokenStream {
+        let type_identifier = self.get_type_identifier(&node.node_url);
+
+        let mut types = types.to_vec();
+        types.sort();
+        types.dedup();
+
+        let mut variants_tokens = quote! {};
+        let mut struct_tokens = quote! {};
+        for value in types {
+            let variant_identifier = format_ident!("{}", to_variant_name(&[value.as_str()]));
+            let payload_tokens = match value {
+                IntermediateType::Null => {
+                    variants_tokens.extend(quote! {
+                        #variant_identifier,
+                    });
+                    continue;
+                }
+                IntermediateType::Boolean => quote! { bool },
+                IntermediateType::Integer => quote! { i64 },
+                IntermediateType::Number => quote! { f64 },
+                IntermediateType::String => quote! { String },
+                IntermediateType::Array => {
+                    let item_tokens = self.get_items_type(node);
+                    quote! { Vec<#item_tokens> }
+                }
+                IntermediateType::Object if self.is_struct(node) => {
+                    let struct_identifier = self.get_object_variant_identifier(&node.node_url);
+                    struct_tokens = self.generate_struct_token_stream(node, &struct_identifier);
+                    struct_identifier
+                }
+                IntermediateType::Object => self.generate_map_type_expression(node),
+            };
+            variants_tokens.extend(quote! {
+                #variant_identifier(#payload_tokens),
+            });
+        }
+
+        self.definitions.insert(
+            node.node_url.clone(),
+            quote! {
+                #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+                #[serde(untagged)]
+                pub enum #type_identifier {
+                    #variants_tokens
+                }
+                #struct_tokens
+            },
+        );
+
+        type_identifier
+    }
+
+    // The struct of the object variant, named after the enum. It is not the
+    // type of a node, so it gets a name that no node has.
+    fn get_object_variant_identifier(&self, node_url: &Url) -> TokenStream {
+        let base_name = format!("{}Object", self.names.get(node_url).unwrap());
+
+        let mut name = base_name.clone();
+        let mut index = 1;
+        while self.names.values().any(|other| *other == name) {
+            index += 1;
+            name = format!("{}{}", base_name, index);
+        }
+
+        let identifier = format_ident!("{}", name);
+        quote! { #identifier }
+    }
+
+    fn generate_variant_doc_token_stream(&self, member: &IntermediateNode) -> TokenStream {
+        match &member.description {
+            Some(description) => {
+                let description = format!(" {}", description);
+                quote! {
+                    #[doc = #description]
+                }
+            }
+            None => quote! {},
+        }
+    }
+
+    // The title of the member, the name of the referenced type, the constant
+    // the member is or its type. The index only when there is nothing else.
+    fn get_variant_name(&self, member: &IntermediateNode, index: usize) -> String {
+        if let Some(title) = &member.title {
+            return to_variant_name(&[title]);
+        }
+
+        if let Some(name) = member
+            .select_reference()
+            .and_then(|node_ref_url| self.names.get(node_ref_url))
+        {
+            return name.clone();
+        }
+
+        if let Some(value) = self.select_constant_string(&member.node_url) {
+            return to_variant_name(&[&value]);
+        }
+
+        if let Some(value) = member.select_single_type() {
+            return to_variant_name(&[value.as_str()]);
+        }
+
+        format!("Variant{}", index)
+    }
+
+    // The property that has a different constant string in every member,
+    // with those strings. It does not have to be required, it is how the
+    // members are told apart.
+    fn select_union_tag(&self, members: &[&IntermediateNode]) -> Option<(String, Vec<String>)> {
+        let first = members.first()?;
+
+        first.properties.iter().find_map(|(name, _url)| {
+            let mut values = Vec::new();
+            for member in members {
+                let (_name, property_url) = member
+                    .properties
+                    .iter()
+                    .find(|(other, _url)| other == name)?;
+                let value = self.select_constant_string(property_url)?;
+                if values.contains(&value) {
+                    return None;
+                }
+                values.push(value);
+            }
+            Some((name.clone(), values))
+        })
+    }
+
+    // A member that is an object with properties of its own, not a reference,
+    // so its struct can leave the tag out.
+    fn is_tagged_member_struct(&self, member: &IntermediateNode) -> bool {
+        member.select_reference().is_none()
+            && self.select_union_members(member).is_none()
+            && member
+                .types
+                .as_ref()
+                .is_none_or(|types| types.len() == 1 && types[0] == IntermediateType::Object)
+            && !member.properties.is_empty()
+    }
+
+    fn select_constant_string(&self, node_url: &Url) -> Option<String> {
+        let node = self.resolve_node(node_url)?;
+
+        if let Some(value) = &node.const_value {
+            return value.as_str().map(str::to_owned);
+        }
+
+        match node.enum_values.as_deref() {
+            Some([value]) => value.as_str().map(str::to_owned),
+            _ => None,
+        }
+    }
+
+    // The node at the end of a chain of references.
+    fn resolve_node(&self, node_url: &Url) -> Option<&'b IntermediateNode> {
+        let nodes = self.nodes;
+        let mut node = nodes.get(node_url)?;
+        let mut visited = vec![node_url];
+
+        while let Some(node_ref_url) = node.select_reference() {
+            if visited.contains(&node_ref_url) {
+                return None;
+            }
+            visited.push(node_ref_url);
+            node = nodes.get(node_ref_url)?;
+        }
+
+        Some(node)
+    }
+
+    // A struct when the properties are known, a map otherwise.
+    fn generate_object_type_expression(&mut self, node: &IntermediateNode) -> TokenStream {
+        if !self.is_struct(node) {
+            return self.generate_map_type_expression(node);
+        }
+
+        let type_identifier = self.get_type_identifier(&node.node_url);
+        let tokens = self.generate_struct_token_stream(node, &type_identifier);
+        self.definitions.insert(node.node_url.clone(), tokens);
+
+        type_identifier
+    }
+
+    fn generate_map_type_expression(&mut self, node: &IntermediateNode) -> TokenStream {
+        let value_tokens = self
+            .get_additional_properties_type(node)
+            .unwrap_or_else(|| quote! { serde_json::Value });
+        quote! { std::collections::HashMap<String, #value_tokens> }
+    }
+
+    fn generate_struct_token_stream(
+        &mut self,
+        node: &IntermediateNode,
+        type_identifier: &TokenStream,
+    ) -> TokenStream {
+        let tag = self.tagged_members.get(&node.node_url).cloned();
+        let fields_tokens = self.generate_fields_token_stream(node, tag.as_deref(), quote! { pub });
+
+        let mut tokens = quote! {
+            #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        };
+        if self.is_closed_object(node) {
+            tokens.extend(quote! {
+                #[serde(deny_unknown_fields)]
+            });
+        }
+        tokens.extend(quote! {
+            pub struct #type_identifier {
+                #fields_tokens
+            }
+        });
+
+        tokens
+    }
+
+    fn is_struct(&self, node: &IntermediateNode) -> bool {
+        !node.properties.is_empty() || !node.required.is_empty() || self.is_closed_object(node)
+    }
+
+    // additionalProperties false without pattern properties
+    fn is_closed_object(&self, node: &IntermediateNode) -> bool {
+        !self.allows_additional_properties(node) && node.pattern_properties.is_empty()
+    }
+
+    fn allows_additional_properties(&self, node: &IntermediateNode) -> bool {
+        node.additional_properties
+            .as_ref()
+            .and_then(|url| self.nodes.get(url))
+            .and_then(|additional_properties| additional_properties.boolean)
+            != Some(false)
+    }
+
+    // The type of the properties that are not fields. With pattern properties
+    // that is their type when they all have the same one, names that match no
+    // pattern are expected to have it too unless additional properties say
+    // otherwise.
+    fn get_additional_properties_type(&mut self, node: &IntermediateNode) -> Option<TokenStream> {
+        if !node.pattern_properties.is_empty() {
+            let mut value_urls: Vec<_> =
+                node.pattern_properties.iter().map(|(_, url)| url).collect();
+            if self.allows_additional_properties(node) {
+                value_urls.extend(node.additional_properties.as_ref());
+            }
+
+            // references to the same node have the same type
+            let mut value_tokens: Vec<_> = value_urls
+                .into_iter()
+                .map(|value_url| {
+                    let value_url = self
+                        .resolve_node(value_url)
+                        .map_or(value_url, |value_node| &value_node.node_url);
+                    self.get_indirect_type_reference(value_url)
+                })
+                .collect();
+            value_tokens.dedup_by_key(|tokens| tokens.to_string());
+            if value_tokens.len() == 1 {
+                return value_tokens.pop();
+            }
+
+            return Some(quote! { serde_json::Value });
+        }
+
+        match &node.additional_properties {
+            Some(additional_properties_url) if self.allows_additional_properties(node) => {
+                Some(self.get_indirect_type_reference(additional_properties_url))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_items_type(&mut self, node: &IntermediateNode) -> TokenStream {
+        match &node.items {
+            Some(items_url) => self.get_indirect_type_reference(items_url),
+            None => quote! { serde_json::Value },
+        }
+    }
+
+    // a reference from a type that stores it on the heap, like a Vec
+    fn get_indirect_type_reference(&mut self, node_url: &Url) -> TokenStream {
+        self.indirections += 1;
+        let tokens = self.get_type_reference(node_url);
+        self.indirections -= 1;
+
+        tokens
+    }
+
+    // The fields of a struct, or of a struct variant without the tag
+    // property.
+    fn generate_fields_token_stream(
+        &mut self,
+        node: &IntermediateNode,
+        tag: Option<&str>,
+        visibility: TokenStream,
+    ) -> TokenStream {
+        let mut field_names = Vec::new();
+        let mut tokens = quote! {};
+
+        // required properties without a schema are fields too
+        let mut property_urls: Vec<_> = node
+            .properties
+            .iter()
+            .map(|(name, url)| (name, Some(url)))
+            .collect();
+        property_urls.extend(
+            node.required
+                .iter()
+                .filter(|name| node.properties.iter().all(|(other, _url)| other != *name))
+                .map(|name| (name, None)),
+        );
+
+        for (name, property_url) in property_urls {
+            if Some(name.as_str()) == tag {
+                continue;
+            }
+
+            // a property that is not allowed, like one that is merged with
+            // additionalProperties false, is never there
+            let is_allowed = property_url
+                .and_then(|url| self.nodes.get(url))
+                .is_none_or(|property| property.boolean != Some(false));
+            if !is_allowed && !node.required.contains(name) {
+                continue;
+            }
+
+            let field_name = get_unique_member_name(&mut field_names, name);
+            let field_identifier = format_ident!("{}", field_name);
+
+            let mut field_type_tokens = match property_url {
+                Some(property_url) => self.get_type_reference(property_url),
+                None => quote! { serde_json::Value },
+            };
+            let mut attributes = Vec::new();
+
+            if field_name != *name {
+                attributes.push(quote! { rename = #name });
+            }
+
+            if !node.required.contains(name) {
+                field_type_tokens = quote! { Option<#field_type_tokens> };
+                attributes.push(quote! { default, skip_serializing_if = "Option::is_none" });
+            }
+
+            if let Some(description) = property_url
+                .and_then(|url| self.nodes.get(url))
+                .and_then(|property| property.description.as_ref())
+            {
+                let description = format!(" {}", description);
+                tokens.extend(quote! {
+                    #[doc = #description]
+                });
+            }
+
+            if !attributes.is_empty() {
+                tokens.extend(quote! {
+                    #[serde(#(#attributes),*)]
+                });
+            }
+
+            tokens.extend(quote! {
+                #visibility #field_identifier: #field_type_tokens,
+            });
+        }
+
+        if let Some(value_tokens) = self.get_additional_properties_type(node) {
+            let field_name = get_unique_member_name(&mut field_names, "additional_properties");
+            let field_identifier = format_ident!("{}", field_name);
+
+            tokens.extend(quote! {
+                #[serde(flatten)]
+                #visibility #field_identifier: std::collections::HashMap<String, #value_tokens>,
+            });
+        }
+
+        tokens
+    }
+}
+
+// properties like a-b and a_b have the same member name, the later ones get a
+// number appended
+fn get_unique_member_name(member_names: &mut Vec<String>, text: &str) -> String {
+    let base_name = to_member_name(text);
+
+    let mut name = base_name.clone();
+    let mut index = 1;
+    while member_names.contains(&name) {
+        index += 1;
+        name = format!("{}_{}", base_name.trim_end_matches('_'), index);
+    }
+
+    member_names.push(name.clone());
+    name
+}
+
+// members with the same name get a number appended
+fn get_unique_variant_name(variant_names: &mut Vec<String>, base_name: String) -> String {
+    let mut name = base_name.clone();
+    let mut index = 1;
+    while variant_names.contains(&name) {
+        index += 1;
+        name = format!("{}{}", base_name, index);
+    }
+
+    variant_names.push(name.clone());
+    name
+}
+
+// SchemaParameter and SchemaReference become Parameter and Reference, as long
+// as every name keeps a word and does not start with a digit
+fn strip_common_prefix(names: Vec<String>) -> Vec<String> {
+    if names.len() < 2 {
+        return names;
+    }
+
+    let word_starts = |name: &str| -> Vec<usize> {
+        name.char_indices()
+            .filter(|(index, character)| *index > 0 && character.is_ascii_uppercase())
+            .map(|(index, _character)| index)
+            .collect()
+    };
+
+    let first = &names[0];
+    let prefix_length = word_starts(first)
+        .into_iter()
+        .rev()
+        .find(|length| {
+            names.iter().all(|name| {
+                name.starts_with(&first[..*length])
+                    && word_starts(name).contains(length)
+                    && !name[*length..].starts_with(|character: char| character.is_ascii_digit())
+            })
+        })
+        .unwrap_or_default();
+
+    names
+        .into_iter()
+        .map(|name| name[prefix_length..].to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::package::format_file_token_stream;
+    use crate::schemas::meta::MetaSchemaId;
+    use crate::utils::value_rc::ValueRc;
+    use std::rc::Rc;
+
+    fn generate(source: &str) -> String {
+        let node: ValueRc = serde_json::from_str(source).unwrap();
+        let node_url: Url = "https://example.com/object".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        format_file_token_stream(generate_types(&manager, 0).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generate_structs() {
+        let types = generate(
+            r#"
+            {
+                "type": "object",
+                "required": ["type", "a-b"],
+                "properties": {
+                    "type": { "type": "string" },
+                    "a-b": { "type": "integer" },
+                    "a_b": { "type": "boolean" },
+                    "closed": {
+                        "type": "object",
+                        "properties": { "x": true },
+                        "additionalProperties": false
+                    }
+                },
+                "additionalProperties": { "type": "number" }
+            }
+            "#,
+        );
+
+        assert!(types.contains(
+            r#"#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Object {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(rename = "a-b")]
+    pub a_b: i64,
+    #[serde(rename = "a_b", default, skip_serializing_if = "Option::is_none")]
+    pub a_b_2: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<ObjectClosed>,
+    #[serde(flatten)]
+    pub additional_properties: std::collections::HashMap<String, f64>,
+}"#
+        ));
+        assert!(types.contains(
+            r#"#[serde(deny_unknown_fields)]
+pub struct ObjectClosed {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<ObjectClosedX>,
+}"#
+        ));
+    }
+
+    #[test]
+    fn generate_enums() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "properties": {
+                    "value": { "type": ["string", "null", "integer"] },
+                    "item": {
+                        "oneOf": [
+                            { "$ref": "#/$defs/item-reference" },
+                            { "$ref": "#/$defs/item-value", "description": "An item" }
+                        ]
+                    },
+                    "shape": {
+                        "anyOf": [
+                            {
+                                "required": ["kind", "radius"],
+                                "properties": {
+                                    "kind": { "const": "circle" },
+                                    "radius": { "type": "number" }
+                                }
+                            },
+                            {
+                                "required": ["kind"],
+                                "properties": {
+                                    "kind": { "enum": ["square"] },
+                                    "size": { "type": "number" }
+                                }
+                            }
+                        ]
+                    }
+                },
+                "$defs": {
+                    "item-reference": { "type": "string" },
+                    "item-value": { "type": "integer" }
+                }
+            }
+            "##,
+        );
+
+        assert!(types.contains(
+            r#"#[serde(untagged)]
+pub enum ObjectValue {
+    Null,
+    Integer(i64),
+    String(String),
+}"#
+        ));
+        assert!(types.contains(
+            r#"#[serde(untagged)]
+pub enum ObjectItem {
+    Reference(String),
+    /// An item
+    Value(i64),
+}"#
+        ));
+        assert!(types.contains(
+            r#"#[serde(tag = "kind")]
+pub enum ObjectShape {
+    #[serde(rename = "circle")]
+    Circle(ObjectShapeAnyOf0),
+    #[serde(rename = "square")]
+    Square(ObjectShapeAnyOf1),
+}"#
+        ));
+        // the tag is not a field of the payload
+        assert!(types.contains(
+            r#"pub struct ObjectShapeAnyOf0 {
+    pub radius: f64,
+}"#
+        ));
+    }
+
+    #[test]
+    fn generate_enum_variants() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "properties": {
+                    "location": {
+                        "oneOf": [
+                            { "properties": { "in": { "enum": ["path"] } } },
+                            { "$ref": "#/$defs/query" }
+                        ]
+                    },
+                    "mode": {
+                        "oneOf": [
+                            { "type": "string", "const": "on" },
+                            { "title": "Level", "type": "integer" },
+                            { "type": "object", "properties": { "a": true } }
+                        ]
+                    },
+                    "value": {
+                        "type": ["object", "string"],
+                        "properties": { "a": { "type": "integer" } }
+                    }
+                },
+                "$defs": {
+                    "query": {
+                        "required": ["in"],
+                        "properties": {
+                            "in": { "const": "query" },
+                            "name": { "type": "string" }
+                        }
+                    }
+                }
+            }
+            "##,
+        );
+
+        // a tag that is not required, a referenced struct has the tag as a
+        // field so its fields are inlined
+        assert!(types.contains(
+            r#"#[serde(tag = "in")]
+pub enum ObjectLocation {
+    #[serde(rename = "path")]
+    Path(ObjectLocationOneOf0),
+    #[serde(rename = "query")]
+    Query {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+}"#
+        ));
+        assert!(types.contains(
+            r#"#[serde(untagged)]
+pub enum ObjectMode {
+    On(String),
+    Level(i64),
+    Object(ObjectModeOneOf2),
+}"#
+        ));
+        assert!(types.contains(
+            r#"pub enum ObjectValue {
+    String(String),
+    Object(ObjectValueObject),
+}
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ObjectValueObject {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a: Option<i64>,
+}"#
+        ));
+    }
+
+    #[test]
+    fn generate_draft_07_reference_siblings() {
+        let types = generate(
+            r##"
+            {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "a": {
+                        "$ref": "#/definitions/a",
+                        "type": "object",
+                        "properties": { "b": { "type": "string" } }
+                    }
+                },
+                "definitions": {
+                    "a": { "type": "integer" }
+                }
+            }
+            "##,
+        );
+
+        // a is the integer it refers to, not an object with a property b
+        assert!(types.contains("pub type ObjectPropertiesA = i64;"));
+        assert!(!types.contains("pub struct ObjectPropertiesA"));
+    }
+
+    #[test]
+    fn generate_reserved_names() {
+        let types = generate(
+            r##"
+            {
+                "$id": "https://example.com/option",
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": { "title": "String", "type": "string" },
+                    "list": { "title": "Vec", "type": "array", "items": { "$ref": "#" } }
+                }
+            }
+            "##,
+        );
+
+        assert!(types.contains("pub struct OptionType {"));
+        assert!(types.contains("pub type StringType = String;"));
+        assert!(types.contains("pub type VecType = Vec<OptionListItems>;"));
+        assert!(types.contains("pub list: Option<VecType>,"));
+    }
+
+    #[test]
+    fn generate_reference_siblings() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "required": ["a"],
+                "properties": {
+                    "a": { "type": "string" }
+                },
+                "$ref": "#/$defs/base",
+                "$defs": {
+                    "base": {
+                        "type": "object",
+                        "properties": {
+                            "b": { "type": "integer" }
+                        }
+                    },
+                    "alias": { "$ref": "#/$defs/base", "description": "The base" }
+                }
+            }
+            "##,
+        );
+
+        assert!(types.contains(
+            r#"pub struct Object {
+    pub a: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b: Option<i64>,
+}"#
+        ));
+        assert!(types.contains("pub type ObjectAlias = ObjectBase;"));
+    }
+
+    #[test]
+    fn generate_pattern_properties() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "properties": {
+                    "same": {
+                        "type": "object",
+                        "patternProperties": {
+                            "^a": { "$ref": "#/$defs/value" },
+                            "^b": { "$ref": "#/$defs/value" }
+                        }
+                    },
+                    "mixed": {
+                        "type": "object",
+                        "patternProperties": {
+                            "^a": { "$ref": "#/$defs/value" },
+                            "^x-": {}
+                        }
+                    }
+                },
+                "$defs": {
+                    "value": {
+                        "oneOf": [{ "type": "string" }, { "type": "integer" }]
+                    }
+                }
+            }
+            "##,
+        );
+
+        assert!(
+            types.contains("pub type ObjectSame = std::collections::HashMap<String, ObjectValue>;")
+        );
+        assert!(types.contains(
+            "pub type ObjectMixed = std::collections::HashMap<String, serde_json::Value>;"
+        ));
+    }
+
+    #[test]
+    fn generate_recursive_types() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "properties": {
+                    "parent": { "$ref": "#" },
+                    "children": { "type": "array", "items": { "$ref": "#" } },
+                    "list": { "$ref": "#/$defs/list" }
+                },
+                "$defs": {
+                    "list": { "type": "array", "items": { "$ref": "#/$defs/list" } }
+                }
+            }
+            "##,
+        );
+
+        assert!(types.contains("pub type ObjectParent = Box<Object>;"));
+        assert!(types.contains("pub type ObjectChildrenItems = Object;"));
+        // an alias can not refer to itself
+        assert!(types.contains("pub type ObjectListItems = serde_json::Value;"));
+    }
+
+    #[test]
+    fn generate_all_of_structs() {
+        let types = generate(
+            r##"
+            {
+                "type": "object",
+                "required": ["dog", "tagged"],
+                "properties": {
+                    "dog": {
+                        "allOf": [
+                            { "$ref": "#/$defs/pet" },
+                            {
+                                "properties": {
+                                    "bark": { "type": "boolean" }
+                                }
+                            }
+                        ]
+                    },
+                    "tagged": {
+                        "allOf": [
+                            {
+                                "properties": { "id": { "type": "integer" } },
+                                "additionalProperties": { "type": "string" }
+                            },
+                            {
+                                "required": ["name"],
+                                "properties": { "name": true }
+                            }
+                        ]
+                    }
+                },
+                "$defs": {
+                    "pet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": { "type": "string" }
+                        },
+                        "additionalProperties": false
+                    }
+                }
+            }
+            "##,
+        );
+
+        // the additionalProperties of pet do not allow bark
+        assert!(types.contains(
+            r#"#[serde(deny_unknown_fields)]
+pub struct ObjectDog {
+    pub name: String,
+}"#
+        ));
+        assert!(types.contains(
+            r#"#[serde(deny_unknown_fields)]
+pub struct ObjectPet {
+    pub name: String,
+}"#
+        ));
+
+        // the additionalProperties of the first member apply to name
+        assert!(types.contains(
+            r#"pub struct ObjectTagged {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub name: String,
+    #[serde(flatten)]
+    pub additional_properties: std::collections::HashMap<String, String>,
+}"#
+        ));
+    }
+}
@@ -1 +1,50 @@
+//! Generate rust types and validators from json schema.
+//!
+//! Load a schema, and everything it references, into a [`Manager`], then
+//! generate a package from it with [`generate_package`]. To generate types
+//! from a build script use the [`Builder`], or import them inline with the
+//! `import_schema!` macro of the `jns42` crate.
+//!
+//! ```no_run
+//! use jns42_generator::{generate_package, Manager, MetaSchemaId, PackageOptions};
+//! use std::path::Path;
+//! use url::Url;
+//!
+//! let schema_url: Url = "https://example.com/schema.json".parse().unwrap();
+//!
+//! let mut manager = Manager::new();
+//! manager
+//!     .load_from_url(&schema_url, &schema_url, MetaSchemaId::Draft202012)
+//!     .unwrap();
+//!
+//! generate_package(
+//!     &manager,
+//!     Path::new("schema-package"),
+//!     &PackageOptions {
+//!         package_name: "schema-package",
+//!         package_version: "0.1.0",
+//!         generate_test: false,
+//!         unique_name_seed: 0,
+//!     },
+//! )
+//! .unwrap();
+//! ```
 
+pub mod builder;
+pub mod error;
+pub mod generators;
+pub mod schemas;
+pub mod utils;
+pub mod validators;
+
+pub use builder::{schema_url_from_arg, Builder};
+pub use error::Error;
+pub use generators::{
+    package::{generate_package, PackageOptions},
+    types::generate_types,
+};
+pub use schemas::{loader::Loader, manager::Manager, meta::MetaSchemaId};
+pub use validators::{
+    instance::InstanceValidator,
+    output::{OutputFormat, OutputUnit, ValidationError},
+};
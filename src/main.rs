@@ -1,11 +1,35 @@
 mod programs;
-mod schemas;
-mod utils;
 
 use clap::Parser;
+use jns42_generator::Error;
 use programs::{run_program, ProgramOptions};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let options = ProgramOptions::parse();
-    run_program(options).unwrap()
+
+    // 1 is left for commands that ran, but found a problem, like an invalid
+    // instance
+    match run_program(options) {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            print_error(&error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn print_error(error: &Error) {
+    let root_error = error.root_error();
+    eprintln!("error: {}", root_error);
+
+    let mut source = std::error::Error::source(root_error);
+    while let Some(error) = source {
+        eprintln!("  caused by: {}", error);
+        source = error.source();
+    }
+
+    for referrer_url in error.reference_chain().into_iter().rev() {
+        eprintln!("  referenced from: {}", referrer_url);
+    }
 }
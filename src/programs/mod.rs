@@ -1,6 +1,12 @@
 mod package;
+mod validate;
 
 use clap::{Parser, Subcommand};
+use jns42_generator::error::join_url;
+use jns42_generator::{Error, Manager};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use url::Url;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -12,10 +18,59 @@ pub struct ProgramOptions {
 #[derive(Subcommand, Debug)]
 pub enum ProgramCommands {
     Package(package::CommandOptions),
+    Validate(validate::CommandOptions),
 }
 
-pub fn run_program(options: ProgramOptions) -> Result<(), &'static str> {
+pub fn run_program(options: ProgramOptions) -> Result<ExitCode, Error> {
     match options.command {
         ProgramCommands::Package(options) => package::run_command(options),
+        ProgramCommands::Validate(options) => validate::run_command(options),
     }
 }
+
+// the mapped url prefix may be a path relative to the working directory
+fn parse_retrieval_url_mapping(value: &str) -> Result<(String, Url), Error> {
+    let (url_prefix, mapped_url_prefix) =
+        value
+            .split_once('=')
+            .ok_or_else(|| Error::InvalidRetrievalUrlMapping {
+                mapping: value.to_owned(),
+            })?;
+
+    let mapped_url_prefix = join_url(&get_working_directory_url(), mapped_url_prefix)?;
+
+    Ok((url_prefix.to_owned(), mapped_url_prefix))
+}
+
+fn add_retrieval_url_mappings(
+    manager: &mut Manager,
+    retrieval_url_mappings: Vec<(String, Url)>,
+    retrieval_url_mappings_file: Option<PathBuf>,
+) -> Result<(), Error> {
+    if let Some(retrieval_url_mappings_file) = retrieval_url_mappings_file {
+        let retrieval_url_mappings_url = join_url(
+            &get_working_directory_url(),
+            retrieval_url_mappings_file.to_string_lossy().as_ref(),
+        )?;
+        manager.load_retrieval_url_mappings(&retrieval_url_mappings_url)?;
+    }
+    for (url_prefix, mapped_url_prefix) in retrieval_url_mappings {
+        manager.add_retrieval_url_mapping(&url_prefix, &mapped_url_prefix);
+    }
+
+    Ok(())
+}
+
+// unknown keywords do not stop a command, but they may be misspelled
+fn print_unknown_keywords(manager: &Manager) {
+    for warning in manager.get_unknown_keywords() {
+        eprintln!("warning: {}", warning);
+    }
+}
+
+fn get_working_directory_url() -> Url {
+    std::env::current_dir()
+        .ok()
+        .and_then(|path| Url::from_directory_path(path).ok())
+        .expect("the working directory should be an absolute path")
+}
@@ -1,6 +1,8 @@
-use crate::schemas::manager::Manager;
-use crate::schemas::meta::MetaSchemaId;
+use super::{add_retrieval_url_mappings, parse_retrieval_url_mapping, print_unknown_keywords};
 use clap::Parser;
+use jns42_generator::{generate_package, Error, Manager, MetaSchemaId, PackageOptions};
+use std::path::PathBuf;
+use std::process::ExitCode;
 use url::Url;
 
 #[derive(Parser, Debug)]
@@ -11,7 +13,7 @@ pub struct CommandOptions {
     pub default_meta_schema_url: MetaSchemaId,
 
     #[arg(long)]
-    pub package_directory: String,
+    pub package_directory: PathBuf,
 
     #[arg(long)]
     pub package_name: String,
@@ -22,20 +24,54 @@ pub struct CommandOptions {
     #[arg(long)]
     pub generate_test: bool,
 
+    /// Changes the suffixes that make colliding type names unique
     #[arg(long, default_value_t = 0)]
     pub unique_name_seed: usize,
+
+    /// Fetch urls starting with a prefix from another location, like
+    /// https://example.com/=file:///schemas/
+    #[arg(long = "map", value_parser = parse_retrieval_url_mapping)]
+    pub retrieval_url_mappings: Vec<(String, Url)>,
+
+    /// Json file with retrieval url prefixes as keys and the locations to fetch
+    /// them from as values
+    #[arg(long = "map-file")]
+    pub retrieval_url_mappings_file: Option<PathBuf>,
 }
 
-pub fn run_command(options: CommandOptions) -> Result<(), &'static str> {
+pub fn run_command(options: CommandOptions) -> Result<ExitCode, Error> {
     let CommandOptions {
         schema_url,
         default_meta_schema_url,
-        ..
+        package_directory,
+        package_name,
+        package_version,
+        generate_test,
+        unique_name_seed,
+        retrieval_url_mappings,
+        retrieval_url_mappings_file,
     } = options;
 
     let mut manager = Manager::new();
+    add_retrieval_url_mappings(
+        &mut manager,
+        retrieval_url_mappings,
+        retrieval_url_mappings_file,
+    )?;
 
     manager.load_from_url(&schema_url, &schema_url, default_meta_schema_url)?;
+    print_unknown_keywords(&manager);
+
+    generate_package(
+        &manager,
+        &package_directory,
+        &PackageOptions {
+            package_name: package_name.as_str(),
+            package_version: package_version.as_str(),
+            generate_test,
+            unique_name_seed,
+        },
+    )?;
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
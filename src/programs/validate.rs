@@ -0,0 +1,148 @@
+use super::{
+    add_retrieval_url_mappings, get_working_directory_url, parse_retrieval_url_mapping,
+    print_unknown_keywords,
+};
+use clap::Parser;
+use jns42_generator::error::join_url;
+use jns42_generator::utils::value_rc::ValueRc;
+use jns42_generator::{Error, InstanceValidator, Manager, MetaSchemaId, OutputFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use url::Url;
+
+/// Validate json files against a schema. Exits with 0 when every file is
+/// valid, 1 when one is not and 2 when something could not be loaded.
+#[derive(Parser, Debug)]
+pub struct CommandOptions {
+    pub schema_url: Url,
+
+    /// Json files to validate
+    #[arg(required = true)]
+    pub instance_paths: Vec<PathBuf>,
+
+    #[arg(long, default_value_t = MetaSchemaId::Draft202012)]
+    pub default_meta_schema_url: MetaSchemaId,
+
+    /// Fetch urls starting with a prefix from another location, like
+    /// https://example.com/=file:///schemas/
+    #[arg(long = "map", value_parser = parse_retrieval_url_mapping)]
+    pub retrieval_url_mappings: Vec<(String, Url)>,
+
+    /// Json file with retrieval url prefixes as keys and the locations to fetch
+    /// them from as values
+    #[arg(long = "map-file")]
+    pub retrieval_url_mappings_file: Option<PathBuf>,
+
+    /// Print the results in a standard output format, one json document per
+    /// line for every file, instead of a line for every error
+    #[arg(long)]
+    pub output: Option<OutputFormat>,
+}
+
+pub fn run_command(options: CommandOptions) -> Result<ExitCode, Error> {
+    let CommandOptions {
+        schema_url,
+        instance_paths,
+        default_meta_schema_url,
+        retrieval_url_mappings,
+        retrieval_url_mappings_file,
+        output,
+    } = options;
+
+    let mut manager = Manager::new();
+    add_retrieval_url_mappings(
+        &mut manager,
+        retrieval_url_mappings,
+        retrieval_url_mappings_file,
+    )?;
+
+    manager.load_from_url(&schema_url, &schema_url, default_meta_schema_url)?;
+    print_unknown_keywords(&manager);
+
+    // the schema may have an id that differs from the url it was loaded from
+    let node_url =
+        manager
+            .resolve_node_url(&schema_url)
+            .ok_or_else(|| Error::RootNodeNotFound {
+                node_url: schema_url.clone(),
+            })?;
+
+    let mut validator = InstanceValidator::new(&manager)?;
+    let mut valid = true;
+
+    for instance_path in instance_paths {
+        let instance = read_instance(&instance_path)?;
+
+        let unit = validator.evaluate(&node_url, &instance)?;
+        if !unit.valid {
+            valid = false;
+        }
+
+        if let Some(format) = output {
+            println!("{}", unit.to_output(format));
+            continue;
+        }
+
+        let errors = unit.get_errors();
+        if errors.is_empty() {
+            println!("{}: valid", instance_path.display());
+            continue;
+        }
+
+        for error in errors {
+            println!("{}{}", instance_path.display(), error);
+        }
+    }
+
+    if valid {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::from(1))
+    }
+}
+
+fn read_instance(instance_path: &Path) -> Result<ValueRc, Error> {
+    let instance_url = join_url(
+        &get_working_directory_url(),
+        instance_path.to_string_lossy().as_ref(),
+    )?;
+
+    let source = fs::read_to_string(instance_path).map_err(|source| Error::ReadFile {
+        url: instance_url.clone(),
+        source,
+    })?;
+
+    serde_json::from_str(&source).map_err(|source| Error::Deserialize {
+        url: instance_url,
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_against_broken_schema() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/unresolved-reference.json");
+
+        // the command exits with 2 on an error, not with 0 for a valid file
+        let error = run_command(CommandOptions {
+            schema_url: Url::from_file_path(&fixture_path).unwrap(),
+            instance_paths: vec![fixture_path],
+            default_meta_schema_url: MetaSchemaId::Draft202012,
+            retrieval_url_mappings: Vec::new(),
+            retrieval_url_mappings_file: None,
+            output: None,
+        })
+        .unwrap_err();
+
+        assert!(matches!(
+            error.root_error(),
+            Error::Located { source, .. }
+                if matches!(source.as_ref(), Error::UnresolvedReference { .. })
+        ));
+    }
+}
@@ -1,6 +1,9 @@
-use super::meta::META_SCHEMA_ID;
+use super::meta::{KEYWORDS, META_SCHEMA_ID};
 use super::selectors::Selectors;
-use crate::schemas::loader::Loader;
+use crate::error::{join_url, Error};
+use crate::schemas::intermediate::IntermediateNode;
+use crate::schemas::loader::{read_intermediate_node, select_reference_keywords, Loader};
+use crate::utils::json_pointer::json_pointer_to_fragment;
 use crate::utils::value_rc::ValueRc;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -8,46 +11,360 @@ use url::Url;
 
 #[derive(Default)]
 pub struct LoaderImpl {
-    _root_node_map: HashMap<Url, serde_json::Value>,
+    root_node_map: HashMap<Url, Rc<ValueRc>>,
+    node_map: HashMap<Url, Rc<ValueRc>>,
+    anchor_map: HashMap<Url, Url>,
 }
 
 impl LoaderImpl {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn get_node_root_url(node_url: &Url) -> Url {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+        root_node_url
+    }
+
+    // An id is ignored next to a $ref. An id that is just a fragment names
+    // the node, any other id makes the node an embedded resource with its own
+    // url that is the base for everything below it.
+    fn select_node_id(node: &Rc<ValueRc>) -> Option<&str> {
+        if node.select_ref().is_some() {
+            return None;
+        }
+
+        node.select_id()
+    }
+
+    fn get_sub_node_url(
+        node_url: &Url,
+        sub_pointer: &str,
+        sub_node: &Rc<ValueRc>,
+    ) -> Result<Url, Error> {
+        let root_node_url = Self::get_node_root_url(node_url);
+
+        if let Some(sub_node_id) = Self::select_node_id(sub_node) {
+            if !sub_node_id.starts_with('#') {
+                let mut sub_node_url = join_url(&root_node_url, sub_node_id)?;
+                sub_node_url.set_fragment(None);
+
+                return Ok(sub_node_url);
+            }
+        }
+
+        join_url(
+            &root_node_url,
+            format!("#{}", json_pointer_to_fragment(sub_pointer)).as_str(),
+        )
+    }
+
+    fn select_resource_node_entries(
+        node_url: &Url,
+        pointer: &str,
+        node: &Rc<ValueRc>,
+    ) -> Result<Vec<(Url, Rc<ValueRc>)>, Error> {
+        let mut result = vec![(node_url.clone(), node.clone())];
+
+        for (sub_pointer, sub_node) in node.select_sub_nodes(pointer) {
+            let sub_node_url = Self::get_sub_node_url(node_url, sub_pointer.as_str(), &sub_node)?;
+            let sub_pointer = if sub_node_url.fragment().is_none() {
+                ""
+            } else {
+                sub_pointer.as_str()
+            };
+
+            result.extend(Self::select_resource_node_entries(
+                &sub_node_url,
+                sub_pointer,
+                &sub_node,
+            )?);
+        }
+
+        Ok(result)
+    }
 }
 
 impl Loader for LoaderImpl {
     fn is_schema_root_node(&self, node: Rc<ValueRc>) -> bool {
         if let Some(schema) = node.select_schema() {
-            return schema == META_SCHEMA_ID;
+            // the trailing empty fragment is often omitted
+            return schema.trim_end_matches('#') == META_SCHEMA_ID.trim_end_matches('#');
         }
 
         false
     }
 
-    fn load_root_node(&mut self, _node: Rc<ValueRc>, _node_url: &Url) -> Result<(), &'static str> {
-        todo!()
+    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), Error> {
+        if self.root_node_map.insert(node_url.clone(), node).is_some() {
+            return Err(Error::RootNodeAlreadyPresent {
+                node_url: node_url.clone(),
+            });
+        }
+
+        Ok(())
     }
 
-    fn index_root_node(&mut self, _node_url: &Url) -> Result<Vec<Url>, &'static str> {
-        todo!()
+    fn index_root_node(&mut self, root_node_url: &Url) -> Result<Vec<Url>, Error> {
+        let mut result = Vec::new();
+
+        let root_node =
+            self.root_node_map
+                .get(root_node_url)
+                .ok_or_else(|| Error::RootNodeNotFound {
+                    node_url: root_node_url.clone(),
+                })?;
+
+        for (sub_node_url, sub_node) in
+            Self::select_resource_node_entries(root_node_url, "", root_node)?
+        {
+            let resource_node_url = Self::get_node_root_url(&sub_node_url);
+
+            // the id of the root node often ends in an empty fragment, that
+            // is not an anchor
+            if let Some(node_id) = Self::select_node_id(&sub_node) {
+                let anchor_url = join_url(&resource_node_url, node_id)?;
+
+                if anchor_url
+                    .fragment()
+                    .is_some_and(|fragment| !fragment.is_empty())
+                    && self
+                        .anchor_map
+                        .insert(anchor_url.clone(), sub_node_url.clone())
+                        .is_some()
+                {
+                    return Err(Error::DuplicateAnchor { anchor_url });
+                }
+            }
+
+            self.node_map.insert(sub_node_url.clone(), sub_node);
+            result.push(sub_node_url);
+        }
+
+        Ok(result)
     }
 
     fn get_sub_node_urls(
         &self,
-        _node: Rc<ValueRc>,
-        _node_url: &Url,
-        _retrieval_url: &Url,
-    ) -> Result<Vec<(Url, Url)>, &'static str> {
-        todo!()
+        node: Rc<ValueRc>,
+        node_url: &Url,
+        retrieval_url: &Url,
+    ) -> Result<Vec<(Url, Url)>, Error> {
+        let node_url = self.get_root_node_url(node.clone(), node_url)?;
+        let mut result = Vec::new();
+
+        let resource_node_entries = Self::select_resource_node_entries(&node_url, "", &node)?;
+        let resource_node_urls: Vec<_> = resource_node_entries
+            .iter()
+            .map(|(sub_node_url, _sub_node)| sub_node_url)
+            .filter(|sub_node_url| sub_node_url.fragment().is_none())
+            .collect();
+
+        for (sub_node_url, node_ref) in
+            resource_node_entries
+                .iter()
+                .filter_map(|(sub_node_url, sub_node)| {
+                    sub_node
+                        .select_ref()
+                        .map(|value| (sub_node_url, value.to_owned()))
+                })
+        {
+            let node_ref_url =
+                sub_node_url
+                    .join(node_ref.as_str())
+                    .map_err(|source| Error::InvalidReference {
+                        node_url: sub_node_url.clone(),
+                        reference: node_ref.clone(),
+                        source,
+                    })?;
+
+            // references to this document or to one of its embedded resources
+            // do not need to be retrieved
+            let node_ref_root_url = Self::get_node_root_url(&node_ref_url);
+            if resource_node_urls.contains(&&node_ref_root_url) {
+                continue;
+            }
+
+            let mut retrieval_ref_url = join_url(retrieval_url, node_ref.as_str())?;
+            retrieval_ref_url.set_fragment(None);
+
+            result.push((node_ref_url, retrieval_ref_url));
+        }
+
+        Ok(result)
     }
 
-    fn get_root_node_url(
-        &self,
-        _node: Rc<ValueRc>,
-        _default_node_url: &Url,
-    ) -> Result<Url, &'static str> {
-        todo!()
+    fn get_root_node_url(&self, node: Rc<ValueRc>, default_node_url: &Url) -> Result<Url, Error> {
+        let mut node_url: Url;
+
+        let node_id = node.select_id();
+        if let Some(node_id) = node_id {
+            node_url = join_url(default_node_url, node_id)?;
+        } else {
+            node_url = default_node_url.clone();
+        }
+
+        // an id like http://json-schema.org/draft-04/schema# has an empty
+        // fragment, the root node is identified without it
+        node_url.set_fragment(None);
+
+        Ok(node_url)
+    }
+
+    fn select_node(&self, node_url: &Url) -> Option<Rc<ValueRc>> {
+        self.node_map.get(node_url).cloned()
+    }
+
+    fn select_intermediate_node(&self, node_url: &Url) -> Option<IntermediateNode> {
+        let node = self.node_map.get(node_url)?;
+
+        Some(read_intermediate_node(
+            node_url,
+            node,
+            select_reference_keywords(node, KEYWORDS),
+            |sub_pointer, sub_node| Self::get_sub_node_url(node_url, sub_pointer, sub_node).ok(),
+        ))
+    }
+
+    fn select_node_dynamic_ref_url(&self, _node_url: &Url, _dynamic_scope: &[Url]) -> Option<Url> {
+        None
+    }
+
+    fn resolve_anchor_node_url(&self, anchor_node_url: &Url) -> Option<Url> {
+        self.anchor_map.get(anchor_node_url).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{intermediate::IntermediateType, manager::Manager, meta::MetaSchemaId};
+    use std::path::Path;
+
+    #[test]
+    fn load_meta_schema() {
+        let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/draft-04/schema");
+        let retrieval_url = Url::from_file_path(fixture_path).unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft04)
+            .unwrap();
+
+        let root_node_url: Url = "http://json-schema.org/draft-04/schema".parse().unwrap();
+        let max_length_url = root_node_url.join("#/properties/maxLength").unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url)
+                .unwrap()
+                .unwrap()
+                .description,
+            Some("Core schema meta-schema".to_owned())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&max_length_url)
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.join("#/definitions/positiveInteger").unwrap())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(
+                    &root_node_url.join("#/properties/exclusiveMaximum").unwrap()
+                )
+                .unwrap()
+                .unwrap()
+                .types,
+            Some(vec![IntermediateType::Boolean])
+        );
+    }
+
+    #[test]
+    fn load_ids() {
+        let node: ValueRc = serde_json::from_str(
+            r##"
+            {
+                "$schema": "http://json-schema.org/draft-04/schema#",
+                "definitions": {
+                    "a": { "id": "#foo", "type": "string" },
+                    "b": { "$ref": "#foo" },
+                    "c": {
+                        "id": "nested",
+                        "definitions": {
+                            "d": { "id": "#foo", "type": "integer" }
+                        },
+                        "items": { "$ref": "#foo" }
+                    }
+                }
+            }
+            "##,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/ids".parse().unwrap();
+        let nested_node_url: Url = "https://example.com/nested".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft04)
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&node_url.join("#/definitions/b").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(node_url.join("#/definitions/a").unwrap())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&nested_node_url.join("#/items").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(nested_node_url.join("#/definitions/d").unwrap())
+        );
+
+        let node_urls: Vec<_> = manager.get_node_urls().map(|url| url.as_str()).collect();
+        assert!(node_urls.contains(&"https://example.com/nested"));
+        assert!(!node_urls.contains(&"https://example.com/ids#/definitions/c"));
+    }
+
+    #[test]
+    fn load_escaped_pointers() {
+        let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/openapi/openapi_v3-0_2021-09-28.json");
+        let retrieval_url = Url::from_file_path(fixture_path).unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft04)
+            .unwrap();
+
+        let root_node_url: Url = "https://spec.openapis.org/oas/3.0/schema/2021-09-28"
+            .parse()
+            .unwrap();
+        // the property name is ^\/
+        let paths_node_url = root_node_url
+            .join("#/definitions/Paths/patternProperties/%5E%5C~1")
+            .unwrap();
+
+        assert!(manager.get_node_urls().any(|url| *url == paths_node_url));
+        assert_eq!(
+            manager
+                .select_intermediate_node(&paths_node_url)
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.join("#/definitions/PathItem").unwrap())
+        );
     }
 }
@@ -1,9 +1,41 @@
-use crate::utils::value_rc::ValueRc;
+use crate::utils::{json_pointer::join_json_pointer, value_rc::ValueRc};
 use std::rc::Rc;
 
 pub trait Selectors {
     fn select_schema(&self) -> Option<&str>;
     fn select_id(&self) -> Option<&str>;
+    fn select_ref(&self) -> Option<&str>;
+
+    fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
+    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
+
+    fn select_sub_node_definition_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_property_entries(&self, pointer: &str)
+        -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_additional_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_items_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_additional_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_dependency_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_all_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_any_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_one_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
 }
 
 impl Selectors for Rc<ValueRc> {
@@ -12,6 +44,262 @@ impl Selectors for Rc<ValueRc> {
     }
 
     fn select_id(&self) -> Option<&str> {
-        self.as_object()?.get("$id")?.as_str()
+        self.as_object()?.get("id")?.as_str()
+    }
+
+    fn select_ref(&self) -> Option<&str> {
+        self.as_object()?.get("$ref")?.as_str()
+    }
+
+    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
+        let result = self.select_sub_nodes(pointer);
+        vec![
+            result.clone(),
+            result
+                .iter()
+                .flat_map(|(sub_pointer, sub_node)| {
+                    sub_node.select_all_sub_nodes(sub_pointer.as_str())
+                })
+                .collect(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
+        vec![
+            self.select_sub_node_definition_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_property_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_additional_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_pattern_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_items_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_additional_items_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_dependency_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_all_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_any_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_one_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_not_entries(pointer)
+                .unwrap_or_default(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    //
+
+    fn select_sub_node_definition_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "definitions";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
     }
+    fn select_sub_node_property_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "properties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_additional_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "additionalProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        // In draft-04 a boolean is not a schema, it only allows or forbids
+        // additional properties. That is what the boolean schemas of later
+        // drafts do, so it is selected as one.
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "patternProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_items_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "items";
+        let selected = self.as_object()?.get(select_name)?;
+
+        if let Some(selected) = selected.as_array() {
+            let result = selected
+                .iter()
+                .enumerate()
+                .map(|(sub_pointer, sub_node)| {
+                    (
+                        join_json_pointer(
+                            pointer,
+                            &[select_name, sub_pointer.to_string().as_str()],
+                        ),
+                        sub_node.clone(),
+                    )
+                })
+                .collect();
+
+            return Some(result);
+        }
+
+        selected.as_object()?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_additional_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "additionalItems";
+        let selected = self.as_object()?.get(select_name)?;
+
+        // like additionalProperties, a boolean is selected as a schema
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_dependency_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "dependencies";
+        let selected = self.as_object()?.get(select_name)?;
+
+        // property dependencies are arrays of property names, only schema
+        // dependencies are sub nodes
+        let result = selected
+            .as_object()?
+            .iter()
+            .filter(|(_sub_pointer, sub_node)| sub_node.as_object().is_some())
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_all_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "allOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_any_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "anyOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_one_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "oneOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "not";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+
+    //
 }
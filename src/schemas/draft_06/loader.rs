@@ -1,53 +1,219 @@
-use super::meta::META_SCHEMA_ID;
+use super::meta::{KEYWORDS, META_SCHEMA_ID};
 use super::selectors::Selectors;
-use crate::schemas::loader::Loader;
+use crate::error::{join_url, Error};
+use crate::schemas::intermediate::IntermediateNode;
+use crate::schemas::loader::{read_intermediate_node, select_reference_keywords, Loader};
+use crate::utils::json_pointer::json_pointer_to_fragment;
 use crate::utils::value_rc::ValueRc;
 use std::collections::HashMap;
+use std::iter::once;
 use std::rc::Rc;
 use url::Url;
 
 #[derive(Default)]
 pub struct LoaderImpl {
-    _root_node_map: HashMap<Url, serde_json::Value>,
+    root_node_map: HashMap<Url, Rc<ValueRc>>,
+    node_map: HashMap<Url, Rc<ValueRc>>,
 }
 
 impl LoaderImpl {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn get_node_root_url(node_url: &Url) -> Url {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+        root_node_url
+    }
+
+    fn get_sub_node_url(root_node_url: &Url, sub_pointer: &str) -> Url {
+        let mut sub_node_url = root_node_url.clone();
+        if !sub_pointer.is_empty() {
+            sub_node_url.set_fragment(Some(json_pointer_to_fragment(sub_pointer).as_str()));
+        }
+        sub_node_url
+    }
 }
 
 impl Loader for LoaderImpl {
     fn is_schema_root_node(&self, node: Rc<ValueRc>) -> bool {
         if let Some(schema) = node.select_schema() {
-            return schema == META_SCHEMA_ID;
+            // the trailing empty fragment is often omitted
+            return schema.trim_end_matches('#') == META_SCHEMA_ID.trim_end_matches('#');
         }
 
         false
     }
 
-    fn load_root_node(&mut self, _node: Rc<ValueRc>, _node_url: &Url) -> Result<(), &'static str> {
-        todo!()
+    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), Error> {
+        if self.root_node_map.insert(node_url.clone(), node).is_some() {
+            return Err(Error::RootNodeAlreadyPresent {
+                node_url: node_url.clone(),
+            });
+        }
+
+        Ok(())
     }
 
-    fn index_root_node(&mut self, _node_url: &Url) -> Result<Vec<Url>, &'static str> {
-        todo!()
+    fn index_root_node(&mut self, root_node_url: &Url) -> Result<Vec<Url>, Error> {
+        let mut result = Vec::new();
+
+        let root_node =
+            self.root_node_map
+                .get(root_node_url)
+                .ok_or_else(|| Error::RootNodeNotFound {
+                    node_url: root_node_url.clone(),
+                })?;
+
+        self.node_map
+            .insert(root_node_url.clone(), root_node.clone());
+        result.push(root_node_url.clone());
+
+        for (sub_pointer, sub_node) in root_node.select_all_sub_nodes("").into_iter() {
+            let sub_node_url = join_url(
+                root_node_url,
+                format!("#{}", json_pointer_to_fragment(&sub_pointer)).as_str(),
+            )?;
+
+            self.node_map.insert(sub_node_url.clone(), sub_node);
+            result.push(sub_node_url);
+        }
+
+        Ok(result)
     }
 
     fn get_sub_node_urls(
         &self,
-        _node: Rc<ValueRc>,
-        _node_url: &Url,
-        _retrieval_url: &Url,
-    ) -> Result<Vec<(Url, Url)>, &'static str> {
-        todo!()
+        node: Rc<ValueRc>,
+        node_url: &Url,
+        retrieval_url: &Url,
+    ) -> Result<Vec<(Url, Url)>, Error> {
+        let node_url = self.get_root_node_url(node.clone(), node_url)?;
+        let mut result = Vec::new();
+
+        for (sub_pointer, node_ref) in once((String::new(), node.clone()))
+            .chain(node.select_all_sub_nodes(""))
+            .filter_map(|(sub_pointer, sub_node)| {
+                sub_node
+                    .select_ref()
+                    .map(|value| (sub_pointer, value.to_owned()))
+            })
+        {
+            let node_ref_url =
+                node_url
+                    .join(node_ref.as_str())
+                    .map_err(|source| Error::InvalidReference {
+                        node_url: Self::get_sub_node_url(&node_url, sub_pointer.as_str()),
+                        reference: node_ref.clone(),
+                        source,
+                    })?;
+            let mut retrieval_ref_url = join_url(retrieval_url, node_ref.as_str())?;
+            retrieval_ref_url.set_fragment(None);
+
+            result.push((node_ref_url, retrieval_ref_url));
+        }
+
+        Ok(result)
     }
 
-    fn get_root_node_url(
-        &self,
-        _node: Rc<ValueRc>,
-        _default_node_url: &Url,
-    ) -> Result<Url, &'static str> {
-        todo!()
+    fn get_root_node_url(&self, node: Rc<ValueRc>, default_node_url: &Url) -> Result<Url, Error> {
+        let mut node_url: Url;
+
+        let node_id = node.select_id();
+        if let Some(node_id) = node_id {
+            node_url = join_url(default_node_url, node_id)?;
+        } else {
+            node_url = default_node_url.clone();
+        }
+
+        // an id like http://json-schema.org/draft-06/schema# has an empty
+        // fragment, the root node is identified without it
+        node_url.set_fragment(None);
+
+        Ok(node_url)
+    }
+
+    fn select_node(&self, node_url: &Url) -> Option<Rc<ValueRc>> {
+        self.node_map.get(node_url).cloned()
+    }
+
+    fn select_intermediate_node(&self, node_url: &Url) -> Option<IntermediateNode> {
+        let node = self.node_map.get(node_url)?;
+        let root_node_url = Self::get_node_root_url(node_url);
+
+        Some(read_intermediate_node(
+            node_url,
+            node,
+            select_reference_keywords(node, KEYWORDS),
+            |sub_pointer, _sub_node| Some(Self::get_sub_node_url(&root_node_url, sub_pointer)),
+        ))
+    }
+
+    fn select_node_dynamic_ref_url(&self, _node_url: &Url, _dynamic_scope: &[Url]) -> Option<Url> {
+        None
+    }
+
+    fn resolve_anchor_node_url(&self, _anchor_node_url: &Url) -> Option<Url> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{intermediate::IntermediateType, manager::Manager, meta::MetaSchemaId};
+    use std::path::Path;
+
+    #[test]
+    fn load_meta_schema() {
+        let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/draft-06/schema");
+        let retrieval_url = Url::from_file_path(fixture_path).unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft06)
+            .unwrap();
+
+        let root_node_url: Url = "http://json-schema.org/draft-06/schema".parse().unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url)
+                .unwrap()
+                .unwrap()
+                .types,
+            Some(vec![IntermediateType::Object, IntermediateType::Boolean])
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/properties/contains").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.clone())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(
+                    &root_node_url.join("#/properties/propertyNames").unwrap()
+                )
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.clone())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(
+                    &root_node_url.join("#/properties/exclusiveMinimum").unwrap()
+                )
+                .unwrap()
+                .unwrap()
+                .types,
+            Some(vec![IntermediateType::Number])
+        );
     }
 }
@@ -1 +1,48 @@
 pub const META_SCHEMA_ID: &str = "http://json-schema.org/draft-06/schema#";
+
+// the keywords of the intermediate node that this draft has
+pub const KEYWORDS: &[&str] = &[
+    "$ref",
+    "title",
+    "description",
+    "examples",
+    "type",
+    "enum",
+    "const",
+    "multipleOf",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "items",
+    "additionalItems",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "propertyNames",
+    "required",
+    "dependencies",
+    "minProperties",
+    "maxProperties",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+];
+
+// the other keywords of this draft, identifiers and annotations
+pub const OTHER_KEYWORDS: &[&str] = &["$schema", "$id", "definitions", "default"];
+
+// the meta-schema and its vocabularies, by retrieval url
+pub const META_SCHEMA_DOCUMENTS: &[(&str, &str)] = &[(
+    "http://json-schema.org/draft-06/schema",
+    include_str!("../../../fixtures/draft-06/schema"),
+)];
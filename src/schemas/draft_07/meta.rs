@@ -1 +1,61 @@
 pub const META_SCHEMA_ID: &str = "http://json-schema.org/draft-07/schema#";
+
+// the keywords of the intermediate node that this draft has
+pub const KEYWORDS: &[&str] = &[
+    "$ref",
+    "title",
+    "description",
+    "examples",
+    "type",
+    "enum",
+    "const",
+    "multipleOf",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "items",
+    "additionalItems",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "propertyNames",
+    "required",
+    "dependencies",
+    "minProperties",
+    "maxProperties",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+];
+
+// the other keywords of this draft, identifiers and annotations
+pub const OTHER_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "definitions",
+    "default",
+    "readOnly",
+    "writeOnly",
+    "contentEncoding",
+    "contentMediaType",
+];
+
+// the meta-schema and its vocabularies, by retrieval url
+pub const META_SCHEMA_DOCUMENTS: &[(&str, &str)] = &[(
+    "http://json-schema.org/draft-07/schema",
+    include_str!("../../../fixtures/draft-07/schema"),
+)];
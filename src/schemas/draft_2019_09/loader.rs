@@ -1,20 +1,47 @@
-use super::meta::META_SCHEMA_ID;
+use super::meta::{KEYWORDS, META_SCHEMA_ID};
 use super::selectors::Selectors;
-use crate::schemas::loader::Loader;
+use crate::error::{join_url, Error};
+use crate::schemas::intermediate::IntermediateNode;
+use crate::schemas::loader::{read_intermediate_node, Loader};
+use crate::utils::json_pointer::json_pointer_to_fragment;
 use crate::utils::value_rc::ValueRc;
 use std::collections::HashMap;
+use std::iter::once;
 use std::rc::Rc;
 use url::Url;
 
 #[derive(Default)]
 pub struct LoaderImpl {
-    _root_node_map: HashMap<Url, serde_json::Value>,
+    root_node_map: HashMap<Url, Rc<ValueRc>>,
+    node_map: HashMap<Url, Rc<ValueRc>>,
+    anchor_map: HashMap<Url, Url>,
 }
 
 impl LoaderImpl {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn get_node_root_url(node_url: &Url) -> Url {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+        root_node_url
+    }
+
+    fn get_sub_node_url(root_node_url: &Url, sub_pointer: &str) -> Url {
+        let mut sub_node_url = root_node_url.clone();
+        if !sub_pointer.is_empty() {
+            sub_node_url.set_fragment(Some(json_pointer_to_fragment(sub_pointer).as_str()));
+        }
+        sub_node_url
+    }
+
+    fn has_recursive_anchor(&self, root_node_url: &Url) -> bool {
+        self.root_node_map
+            .get(root_node_url)
+            .and_then(|root_node| root_node.select_recursive_anchor())
+            .unwrap_or(false)
+    }
 }
 
 impl Loader for LoaderImpl {
@@ -26,28 +53,279 @@ impl Loader for LoaderImpl {
         false
     }
 
-    fn load_root_node(&mut self, _node: Rc<ValueRc>, _node_url: &Url) -> Result<(), &'static str> {
-        todo!()
+    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), Error> {
+        if self.root_node_map.insert(node_url.clone(), node).is_some() {
+            return Err(Error::RootNodeAlreadyPresent {
+                node_url: node_url.clone(),
+            });
+        }
+
+        Ok(())
     }
 
-    fn index_root_node(&mut self, _node_url: &Url) -> Result<Vec<Url>, &'static str> {
-        todo!()
+    fn index_root_node(&mut self, root_node_url: &Url) -> Result<Vec<Url>, Error> {
+        let mut result = Vec::new();
+
+        let root_node =
+            self.root_node_map
+                .get(root_node_url)
+                .ok_or_else(|| Error::RootNodeNotFound {
+                    node_url: root_node_url.clone(),
+                })?;
+
+        for (sub_pointer, sub_node) in
+            once((String::new(), root_node.clone())).chain(root_node.select_all_sub_nodes(""))
+        {
+            let sub_node_url = if sub_pointer.is_empty() {
+                root_node_url.clone()
+            } else {
+                join_url(
+                    root_node_url,
+                    format!("#{}", json_pointer_to_fragment(&sub_pointer)).as_str(),
+                )?
+            };
+
+            if let Some(anchor) = sub_node.select_anchor() {
+                let anchor_url = join_url(root_node_url, format!("#{}", anchor).as_str())?;
+
+                if self
+                    .anchor_map
+                    .insert(anchor_url.clone(), sub_node_url.clone())
+                    .is_some()
+                {
+                    return Err(Error::DuplicateAnchor { anchor_url });
+                }
+            }
+
+            self.node_map.insert(sub_node_url.clone(), sub_node);
+            result.push(sub_node_url);
+        }
+
+        Ok(result)
     }
 
     fn get_sub_node_urls(
         &self,
-        _node: Rc<ValueRc>,
-        _node_url: &Url,
-        _retrieval_url: &Url,
-    ) -> Result<Vec<(Url, Url)>, &'static str> {
-        todo!()
+        node: Rc<ValueRc>,
+        node_url: &Url,
+        retrieval_url: &Url,
+    ) -> Result<Vec<(Url, Url)>, Error> {
+        let node_url = self.get_root_node_url(node.clone(), node_url)?;
+        let mut result = Vec::new();
+
+        for (sub_pointer, node_ref) in once((String::new(), node.clone()))
+            .chain(node.select_all_sub_nodes(""))
+            .flat_map(|(sub_pointer, sub_node)| {
+                vec![sub_node.select_ref(), sub_node.select_recursive_ref()]
+                    .into_iter()
+                    .flatten()
+                    .map(|value| (sub_pointer.clone(), value.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+        {
+            let node_ref_url =
+                node_url
+                    .join(node_ref.as_str())
+                    .map_err(|source| Error::InvalidReference {
+                        node_url: Self::get_sub_node_url(&node_url, sub_pointer.as_str()),
+                        reference: node_ref.clone(),
+                        source,
+                    })?;
+            let mut retrieval_ref_url = join_url(retrieval_url, node_ref.as_str())?;
+            retrieval_ref_url.set_fragment(None);
+
+            result.push((node_ref_url, retrieval_ref_url));
+        }
+
+        Ok(result)
     }
 
-    fn get_root_node_url(
-        &self,
-        _node: Rc<ValueRc>,
-        _default_node_url: &Url,
-    ) -> Result<Url, &'static str> {
-        todo!()
+    fn get_root_node_url(&self, node: Rc<ValueRc>, default_node_url: &Url) -> Result<Url, Error> {
+        let mut node_url: Url;
+
+        let node_id = node.select_id();
+        if let Some(node_id) = node_id {
+            node_url = join_url(default_node_url, node_id)?;
+        } else {
+            node_url = default_node_url.clone();
+        }
+
+        node_url.set_fragment(None);
+
+        Ok(node_url)
+    }
+
+    fn select_node(&self, node_url: &Url) -> Option<Rc<ValueRc>> {
+        self.node_map.get(node_url).cloned()
+    }
+
+    fn select_intermediate_node(&self, node_url: &Url) -> Option<IntermediateNode> {
+        let node = self.node_map.get(node_url)?;
+        let root_node_url = Self::get_node_root_url(node_url);
+
+        Some(read_intermediate_node(
+            node_url,
+            node,
+            KEYWORDS,
+            |sub_pointer, _sub_node| Some(Self::get_sub_node_url(&root_node_url, sub_pointer)),
+        ))
+    }
+
+    // A $recursiveRef initially resolves like a $ref. When the resource it
+    // resolves to has $recursiveAnchor set, the outermost resource in the
+    // dynamic scope that also has $recursiveAnchor set is the target instead.
+    fn select_node_dynamic_ref_url(&self, node_url: &Url, dynamic_scope: &[Url]) -> Option<Url> {
+        let node = self.node_map.get(node_url)?;
+        let node_ref = node.select_recursive_ref()?;
+        let node_ref_url = node_url.join(node_ref).ok()?;
+
+        let node_ref_root_url = Self::get_node_root_url(&node_ref_url);
+        if !self.has_recursive_anchor(&node_ref_root_url) {
+            return Some(node_ref_url);
+        }
+
+        let dynamic_root_url = dynamic_scope
+            .iter()
+            .find(|root_node_url| self.has_recursive_anchor(root_node_url))
+            .unwrap_or(&node_ref_root_url);
+
+        Some(dynamic_root_url.clone())
+    }
+
+    fn resolve_anchor_node_url(&self, anchor_node_url: &Url) -> Option<Url> {
+        self.anchor_map.get(anchor_node_url).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+    use std::path::Path;
+
+    fn load_fixture(manager: &mut Manager, path: &str) {
+        let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        let retrieval_url = Url::from_file_path(fixture_path).unwrap();
+
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft201909)
+            .unwrap();
+    }
+
+    #[test]
+    fn load_meta_schema() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/draft-2019-09/schema");
+
+        let root_node_url: Url = "https://json-schema.org/draft/2019-09/schema"
+            .parse()
+            .unwrap();
+        let applicator_node_url: Url = "https://json-schema.org/draft/2019-09/meta/applicator"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/allOf/1").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(applicator_node_url.clone())
+        );
+
+        // the recursive reference in the vocabulary points to the outermost
+        // meta-schema, not to the vocabulary itself
+        assert_eq!(
+            manager
+                .select_intermediate_node(&applicator_node_url.join("#/properties/not").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.clone())
+        );
+    }
+
+    #[test]
+    fn load_vocabulary_meta_schema() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/draft-2019-09/meta/applicator");
+
+        let applicator_node_url: Url = "https://json-schema.org/draft/2019-09/meta/applicator"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&applicator_node_url.join("#/properties/not").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(applicator_node_url.clone())
+        );
+    }
+
+    #[test]
+    fn load_anchors() {
+        let node: ValueRc = serde_json::from_str(
+            r##"
+            {
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$defs": {
+                    "a": { "$anchor": "foo", "type": "string" },
+                    "b": { "$ref": "#foo" }
+                },
+                "dependentSchemas": {
+                    "a": { "required": ["b"] }
+                },
+                "unevaluatedProperties": false
+            }
+            "##,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/anchors".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft201909)
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&node_url.join("#/$defs/b").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(node_url.join("#/$defs/a").unwrap())
+        );
+
+        let node_urls: Vec<_> = manager.get_node_urls().map(|url| url.as_str()).collect();
+        assert!(node_urls.contains(&"https://example.com/anchors#/dependentSchemas/a"));
+        assert!(node_urls.contains(&"https://example.com/anchors#/unevaluatedProperties"));
+    }
+
+    #[test]
+    fn load_duplicate_anchors() {
+        let node: ValueRc = serde_json::from_str(
+            r#"
+            {
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$defs": {
+                    "a": { "$anchor": "foo" },
+                    "b": { "$anchor": "foo" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/anchors".parse().unwrap();
+
+        let mut manager = Manager::new();
+        assert!(manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft201909)
+            .is_err());
     }
 }
@@ -1 +1,100 @@
 pub const META_SCHEMA_ID: &str = "https://json-schema.org/draft/2019-09/schema";
+
+// the keywords of the intermediate node that this draft has
+pub const KEYWORDS: &[&str] = &[
+    "$ref",
+    "$recursiveRef",
+    "title",
+    "description",
+    "examples",
+    "type",
+    "enum",
+    "const",
+    "multipleOf",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "items",
+    "additionalItems",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "unevaluatedItems",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "propertyNames",
+    "required",
+    "dependentRequired",
+    "dependentSchemas",
+    "minProperties",
+    "maxProperties",
+    "unevaluatedProperties",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+];
+
+// the other keywords of this draft, identifiers and annotations
+pub const OTHER_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$anchor",
+    "$recursiveAnchor",
+    "$vocabulary",
+    "$comment",
+    "$defs",
+    "definitions",
+    "dependencies",
+    "default",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+];
+
+// the meta-schema and its vocabularies, by retrieval url
+pub const META_SCHEMA_DOCUMENTS: &[(&str, &str)] = &[
+    (
+        "https://json-schema.org/draft/2019-09/schema",
+        include_str!("../../../fixtures/draft-2019-09/schema"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/core",
+        include_str!("../../../fixtures/draft-2019-09/meta/core"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/applicator",
+        include_str!("../../../fixtures/draft-2019-09/meta/applicator"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/validation",
+        include_str!("../../../fixtures/draft-2019-09/meta/validation"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/meta-data",
+        include_str!("../../../fixtures/draft-2019-09/meta/meta-data"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/format",
+        include_str!("../../../fixtures/draft-2019-09/meta/format"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/content",
+        include_str!("../../../fixtures/draft-2019-09/meta/content"),
+    ),
+];
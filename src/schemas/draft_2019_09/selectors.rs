@@ -1,9 +1,62 @@
-use crate::utils::value_rc::ValueRc;
+use crate::utils::{json_pointer::join_json_pointer, value_rc::ValueRc};
 use std::rc::Rc;
 
 pub trait Selectors {
     fn select_schema(&self) -> Option<&str>;
     fn select_id(&self) -> Option<&str>;
+    fn select_ref(&self) -> Option<&str>;
+    fn select_recursive_ref(&self) -> Option<&str>;
+    fn select_anchor(&self) -> Option<&str>;
+    fn select_recursive_anchor(&self) -> Option<bool>;
+
+    fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
+    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
+
+    fn select_sub_node_def_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_property_entries(&self, pointer: &str)
+        -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_additional_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_items_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_additional_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_contains_entries(&self, pointer: &str)
+        -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_dependent_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_property_names_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_all_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_any_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_one_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_if_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_then_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_else_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_unevaluated_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_unevaluated_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_content_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
 }
 
 impl Selectors for Rc<ValueRc> {
@@ -14,4 +67,352 @@ impl Selectors for Rc<ValueRc> {
     fn select_id(&self) -> Option<&str> {
         self.as_object()?.get("$id")?.as_str()
     }
+
+    fn select_ref(&self) -> Option<&str> {
+        self.as_object()?.get("$ref")?.as_str()
+    }
+
+    fn select_recursive_ref(&self) -> Option<&str> {
+        self.as_object()?.get("$recursiveRef")?.as_str()
+    }
+
+    fn select_anchor(&self) -> Option<&str> {
+        self.as_object()?.get("$anchor")?.as_str()
+    }
+
+    fn select_recursive_anchor(&self) -> Option<bool> {
+        self.as_object()?.get("$recursiveAnchor")?.as_bool()
+    }
+
+    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
+        let result = self.select_sub_nodes(pointer);
+        vec![
+            result.clone(),
+            result
+                .iter()
+                .flat_map(|(sub_pointer, sub_node)| {
+                    sub_node.select_all_sub_nodes(sub_pointer.as_str())
+                })
+                .collect(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
+        vec![
+            self.select_sub_node_def_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_property_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_additional_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_pattern_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_items_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_additional_items_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_contains_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_dependent_schema_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_property_names_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_all_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_any_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_one_of_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_not_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_if_entries(pointer).unwrap_or_default(),
+            self.select_sub_node_then_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_else_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_unevaluated_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_unevaluated_items_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_content_schema_entries(pointer)
+                .unwrap_or_default(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    //
+
+    fn select_sub_node_def_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "$defs";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_property_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "properties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_additional_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "additionalProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "patternProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_items_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "items";
+        let selected = self.as_object()?.get(select_name)?;
+
+        if let Some(selected) = selected.as_array() {
+            let result = selected
+                .iter()
+                .enumerate()
+                .map(|(sub_pointer, sub_node)| {
+                    (
+                        join_json_pointer(
+                            pointer,
+                            &[select_name, sub_pointer.to_string().as_str()],
+                        ),
+                        sub_node.clone(),
+                    )
+                })
+                .collect();
+
+            return Some(result);
+        }
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_additional_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "additionalItems";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_contains_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "contains";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_dependent_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "dependentSchemas";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_property_names_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "propertyNames";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_all_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "allOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_any_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "anyOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_one_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "oneOf";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "not";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_if_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "if";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_then_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "then";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_else_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "else";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_unevaluated_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "unevaluatedProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_unevaluated_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "unevaluatedItems";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_content_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "contentSchema";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+
+    //
 }
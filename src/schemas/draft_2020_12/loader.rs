@@ -1,6 +1,9 @@
-use super::meta::META_SCHEMA_ID;
+use super::meta::{KEYWORDS, META_SCHEMA_ID};
 use super::selectors::Selectors;
-use crate::schemas::loader::Loader;
+use crate::error::{join_url, Error};
+use crate::schemas::intermediate::IntermediateNode;
+use crate::schemas::loader::{read_intermediate_node, Loader};
+use crate::utils::json_pointer::json_pointer_to_fragment;
 use crate::utils::value_rc::ValueRc;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -10,12 +13,67 @@ use url::Url;
 pub struct LoaderImpl {
     root_node_map: HashMap<Url, Rc<ValueRc>>,
     node_map: HashMap<Url, Rc<ValueRc>>,
+    anchor_map: HashMap<Url, Url>,
+    dynamic_anchor_map: HashMap<Url, Url>,
 }
 
 impl LoaderImpl {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn get_node_root_url(node_url: &Url) -> Url {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+        root_node_url
+    }
+
+    // A sub node with an $id is an embedded resource, it gets its own url and
+    // establishes a new base for everything below it.
+    fn get_sub_node_url(
+        node_url: &Url,
+        sub_pointer: &str,
+        sub_node: &Rc<ValueRc>,
+    ) -> Result<Url, Error> {
+        let root_node_url = Self::get_node_root_url(node_url);
+
+        if let Some(sub_node_id) = sub_node.select_id() {
+            let mut sub_node_url = join_url(&root_node_url, sub_node_id)?;
+            sub_node_url.set_fragment(None);
+
+            return Ok(sub_node_url);
+        }
+
+        join_url(
+            &root_node_url,
+            format!("#{}", json_pointer_to_fragment(sub_pointer)).as_str(),
+        )
+    }
+
+    fn select_resource_node_entries(
+        node_url: &Url,
+        pointer: &str,
+        node: &Rc<ValueRc>,
+    ) -> Result<Vec<(Url, Rc<ValueRc>)>, Error> {
+        let mut result = vec![(node_url.clone(), node.clone())];
+
+        for (sub_pointer, sub_node) in node.select_sub_nodes(pointer) {
+            let sub_node_url = Self::get_sub_node_url(node_url, sub_pointer.as_str(), &sub_node)?;
+            let sub_pointer = if sub_node_url.fragment().is_none() {
+                ""
+            } else {
+                sub_pointer.as_str()
+            };
+
+            result.extend(Self::select_resource_node_entries(
+                &sub_node_url,
+                sub_pointer,
+                &sub_node,
+            )?);
+        }
+
+        Ok(result)
+    }
 }
 
 impl Loader for LoaderImpl {
@@ -27,30 +85,59 @@ impl Loader for LoaderImpl {
         false
     }
 
-    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), &'static str> {
+    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), Error> {
         if self.root_node_map.insert(node_url.clone(), node).is_some() {
-            return Err("root_node already present");
+            return Err(Error::RootNodeAlreadyPresent {
+                node_url: node_url.clone(),
+            });
         }
 
         Ok(())
     }
 
-    fn index_root_node(&mut self, root_node_url: &Url) -> Result<Vec<Url>, &'static str> {
+    fn index_root_node(&mut self, root_node_url: &Url) -> Result<Vec<Url>, Error> {
         let mut result = Vec::new();
 
-        let root_node = self
-            .root_node_map
-            .get(root_node_url)
-            .ok_or("root_node not found")?;
+        let root_node =
+            self.root_node_map
+                .get(root_node_url)
+                .ok_or_else(|| Error::RootNodeNotFound {
+                    node_url: root_node_url.clone(),
+                })?;
 
-        self.node_map
-            .insert(root_node_url.clone(), root_node.clone());
-        result.push(root_node_url.clone());
+        for (sub_node_url, sub_node) in
+            Self::select_resource_node_entries(root_node_url, "", root_node)?
+        {
+            let resource_node_url = Self::get_node_root_url(&sub_node_url);
 
-        for (sub_pointer, sub_node) in root_node.select_all_sub_nodes("").into_iter() {
-            let sub_node_url = root_node_url
-                .join(format!("#{}", sub_pointer).as_str())
-                .map_err(|_error| "could not build sub_node_url")?;
+            if let Some(anchor) = sub_node.select_anchor() {
+                let anchor_url = join_url(&resource_node_url, format!("#{}", anchor).as_str())?;
+
+                if self.dynamic_anchor_map.contains_key(&anchor_url)
+                    || self
+                        .anchor_map
+                        .insert(anchor_url.clone(), sub_node_url.clone())
+                        .is_some()
+                {
+                    return Err(Error::DuplicateAnchor { anchor_url });
+                }
+            }
+
+            if let Some(dynamic_anchor) = sub_node.select_dynamic_anchor() {
+                let dynamic_anchor_url =
+                    join_url(&resource_node_url, format!("#{}", dynamic_anchor).as_str())?;
+
+                if self.anchor_map.contains_key(&dynamic_anchor_url)
+                    || self
+                        .dynamic_anchor_map
+                        .insert(dynamic_anchor_url.clone(), sub_node_url.clone())
+                        .is_some()
+                {
+                    return Err(Error::DuplicateAnchor {
+                        anchor_url: dynamic_anchor_url,
+                    });
+                }
+            }
 
             self.node_map.insert(sub_node_url.clone(), sub_node);
             result.push(sub_node_url);
@@ -64,23 +151,45 @@ impl Loader for LoaderImpl {
         node: Rc<ValueRc>,
         node_url: &Url,
         retrieval_url: &Url,
-    ) -> Result<Vec<(Url, Url)>, &'static str> {
+    ) -> Result<Vec<(Url, Url)>, Error> {
         let node_url = self.get_root_node_url(node.clone(), node_url)?;
         let mut result = Vec::new();
 
-        for node_ref in
-            node.select_all_sub_nodes("")
-                .into_iter()
-                .filter_map(|(_sub_pointer, sub_node)| {
-                    sub_node.select_ref().map(|value| value.to_owned())
+        let resource_node_entries = Self::select_resource_node_entries(&node_url, "", &node)?;
+        let resource_node_urls: Vec<_> = resource_node_entries
+            .iter()
+            .map(|(sub_node_url, _sub_node)| sub_node_url)
+            .filter(|sub_node_url| sub_node_url.fragment().is_none())
+            .collect();
+
+        for (sub_node_url, node_ref) in
+            resource_node_entries
+                .iter()
+                .flat_map(|(sub_node_url, sub_node)| {
+                    vec![sub_node.select_ref(), sub_node.select_dynamic_ref()]
+                        .into_iter()
+                        .flatten()
+                        .map(|value| (sub_node_url, value.to_owned()))
+                        .collect::<Vec<_>>()
                 })
         {
-            let node_ref_url = node_url
-                .join(node_ref.as_str())
-                .map_err(|_error| "could not build node_ref_url")?;
-            let mut retrieval_ref_url = retrieval_url
-                .join(node_ref.as_str())
-                .map_err(|_error| "could not build retrieval_ref_url")?;
+            let node_ref_url =
+                sub_node_url
+                    .join(node_ref.as_str())
+                    .map_err(|source| Error::InvalidReference {
+                        node_url: sub_node_url.clone(),
+                        reference: node_ref.clone(),
+                        source,
+                    })?;
+
+            // references to this document or to one of its embedded resources
+            // do not need to be retrieved
+            let node_ref_root_url = Self::get_node_root_url(&node_ref_url);
+            if resource_node_urls.contains(&&node_ref_root_url) {
+                continue;
+            }
+
+            let mut retrieval_ref_url = join_url(retrieval_url, node_ref.as_str())?;
             retrieval_ref_url.set_fragment(None);
 
             result.push((node_ref_url, retrieval_ref_url));
@@ -89,20 +198,269 @@ impl Loader for LoaderImpl {
         Ok(result)
     }
 
-    fn get_root_node_url(
-        &self,
-        node: Rc<ValueRc>,
-        default_node_url: &Url,
-    ) -> Result<Url, &'static str> {
-        let node_url;
+    fn get_root_node_url(&self, node: Rc<ValueRc>, default_node_url: &Url) -> Result<Url, Error> {
+        let mut node_url: Url;
 
         let node_id = node.select_id();
         if let Some(node_id) = node_id {
-            node_url = node_id.parse().map_err(|_error| "could not parse id")?;
+            node_url = join_url(default_node_url, node_id)?;
         } else {
             node_url = default_node_url.clone();
         }
 
+        node_url.set_fragment(None);
+
         Ok(node_url)
     }
+
+    fn select_node(&self, node_url: &Url) -> Option<Rc<ValueRc>> {
+        self.node_map.get(node_url).cloned()
+    }
+
+    fn select_intermediate_node(&self, node_url: &Url) -> Option<IntermediateNode> {
+        let node = self.node_map.get(node_url)?;
+
+        Some(read_intermediate_node(
+            node_url,
+            node,
+            KEYWORDS,
+            |sub_pointer, sub_node| Self::get_sub_node_url(node_url, sub_pointer, sub_node).ok(),
+        ))
+    }
+
+    // A $dynamicRef initially resolves like a $ref. When it resolves to a
+    // $dynamicAnchor, the outermost resource in the dynamic scope that has a
+    // $dynamicAnchor with the same name is the target instead.
+    fn select_node_dynamic_ref_url(&self, node_url: &Url, dynamic_scope: &[Url]) -> Option<Url> {
+        let node = self.node_map.get(node_url)?;
+        let node_ref = node.select_dynamic_ref()?;
+        let node_ref_url = node_url.join(node_ref).ok()?;
+
+        if !self.dynamic_anchor_map.contains_key(&node_ref_url) {
+            return Some(node_ref_url);
+        }
+
+        let dynamic_anchor = node_ref_url.fragment()?;
+        for root_node_url in dynamic_scope {
+            let mut dynamic_anchor_url = root_node_url.clone();
+            dynamic_anchor_url.set_fragment(Some(dynamic_anchor));
+
+            if let Some(dynamic_node_url) = self.dynamic_anchor_map.get(&dynamic_anchor_url) {
+                return Some(dynamic_node_url.clone());
+            }
+        }
+
+        Some(node_ref_url)
+    }
+
+    fn resolve_anchor_node_url(&self, anchor_node_url: &Url) -> Option<Url> {
+        self.anchor_map
+            .get(anchor_node_url)
+            .or_else(|| self.dynamic_anchor_map.get(anchor_node_url))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+    use std::path::Path;
+
+    fn load_fixture(manager: &mut Manager, path: &str) {
+        let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        let retrieval_url = Url::from_file_path(fixture_path).unwrap();
+
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap();
+    }
+
+    #[test]
+    fn load_meta_schema() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/draft-2020-12/schema");
+
+        let root_node_url: Url = "https://json-schema.org/draft/2020-12/schema"
+            .parse()
+            .unwrap();
+        let applicator_node_url: Url = "https://json-schema.org/draft/2020-12/meta/applicator"
+            .parse()
+            .unwrap();
+        let unevaluated_node_url: Url = "https://json-schema.org/draft/2020-12/meta/unevaluated"
+            .parse()
+            .unwrap();
+
+        // the dynamic reference in the vocabulary points to the outermost
+        // meta-schema, not to the vocabulary itself
+        assert_eq!(
+            manager
+                .select_intermediate_node(&applicator_node_url.join("#/properties/not").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.clone())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(
+                    &unevaluated_node_url
+                        .join("#/properties/unevaluatedItems")
+                        .unwrap()
+                )
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.clone())
+        );
+    }
+
+    #[test]
+    fn load_vocabulary_meta_schema() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/draft-2020-12/meta/applicator");
+
+        let applicator_node_url: Url = "https://json-schema.org/draft/2020-12/meta/applicator"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&applicator_node_url.join("#/properties/not").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(applicator_node_url.clone())
+        );
+    }
+
+    #[test]
+    fn load_dynamic_anchor_without_scope() {
+        let node: ValueRc = serde_json::from_str(
+            r##"
+            {
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "items": { "$dynamicRef": "#item" },
+                "$defs": {
+                    "item": { "$dynamicAnchor": "item", "type": "string" }
+                }
+            }
+            "##,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/list".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&node_url.join("#/items").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(node_url.join("#/$defs/item").unwrap())
+        );
+    }
+
+    #[test]
+    fn load_anchors() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/anchors.json");
+
+        let root_node_url: Url = "https://jns42-generator.org/anchors".parse().unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/properties/name").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(root_node_url.join("#/$defs/name").unwrap())
+        );
+        assert_eq!(
+            manager.resolve_node_url(&root_node_url.join("#tag").unwrap()),
+            Some(root_node_url.join("#/$defs/tag").unwrap())
+        );
+        assert_eq!(
+            manager.resolve_node_url(&root_node_url.join("#nothing").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn load_duplicate_anchors() {
+        let node: ValueRc = serde_json::from_str(
+            r#"
+            {
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$defs": {
+                    "a": { "$anchor": "foo" },
+                    "b": { "$dynamicAnchor": "foo" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/anchors".parse().unwrap();
+
+        let mut manager = Manager::new();
+        assert!(manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .is_err());
+    }
+
+    #[test]
+    fn load_embedded_resource() {
+        let mut manager = Manager::new();
+        load_fixture(&mut manager, "fixtures/embedded-resource.json");
+
+        let root_node_url: Url = "https://jns42-generator.org/embedded-resource"
+            .parse()
+            .unwrap();
+        let tag_node_url: Url = "https://jns42-generator.org/tag".parse().unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/properties/tag").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(tag_node_url.clone())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/properties/tagName").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(tag_node_url.join("#/properties/name").unwrap())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&tag_node_url.join("#/properties/parent").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(tag_node_url.clone())
+        );
+        assert_eq!(
+            manager.resolve_node_url(&tag_node_url.join("#tag").unwrap()),
+            Some(tag_node_url.clone())
+        );
+        assert_eq!(
+            manager.get_dynamic_scope(&tag_node_url),
+            vec![root_node_url.clone(), tag_node_url.clone()]
+        );
+    }
 }
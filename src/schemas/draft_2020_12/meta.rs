@@ -1 +1,106 @@
 pub const META_SCHEMA_ID: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// the keywords of the intermediate node that this draft has
+pub const KEYWORDS: &[&str] = &[
+    "$ref",
+    "$dynamicRef",
+    "title",
+    "description",
+    "examples",
+    "type",
+    "enum",
+    "const",
+    "multipleOf",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "prefixItems",
+    "items",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "unevaluatedItems",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "propertyNames",
+    "required",
+    "dependentRequired",
+    "dependentSchemas",
+    "minProperties",
+    "maxProperties",
+    "unevaluatedProperties",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+];
+
+// the other keywords of this draft, identifiers and annotations
+pub const OTHER_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$anchor",
+    "$dynamicAnchor",
+    "$vocabulary",
+    "$comment",
+    "$defs",
+    "definitions",
+    "dependencies",
+    "$recursiveAnchor",
+    "$recursiveRef",
+    "default",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+];
+
+// the meta-schema and its vocabularies, by retrieval url
+pub const META_SCHEMA_DOCUMENTS: &[(&str, &str)] = &[
+    (
+        "https://json-schema.org/draft/2020-12/schema",
+        include_str!("../../../fixtures/draft-2020-12/schema"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/core",
+        include_str!("../../../fixtures/draft-2020-12/meta/core"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/applicator",
+        include_str!("../../../fixtures/draft-2020-12/meta/applicator"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/unevaluated",
+        include_str!("../../../fixtures/draft-2020-12/meta/unevaluated"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/validation",
+        include_str!("../../../fixtures/draft-2020-12/meta/validation"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/meta-data",
+        include_str!("../../../fixtures/draft-2020-12/meta/meta-data"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/format-annotation",
+        include_str!("../../../fixtures/draft-2020-12/meta/format-annotation"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/content",
+        include_str!("../../../fixtures/draft-2020-12/meta/content"),
+    ),
+];
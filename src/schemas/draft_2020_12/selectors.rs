@@ -5,9 +5,11 @@ pub trait Selectors {
     fn select_schema(&self) -> Option<&str>;
     fn select_id(&self) -> Option<&str>;
     fn select_ref(&self) -> Option<&str>;
+    fn select_dynamic_ref(&self) -> Option<&str>;
+    fn select_anchor(&self) -> Option<&str>;
+    fn select_dynamic_anchor(&self) -> Option<&str>;
 
     fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
-    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)>;
 
     fn select_sub_node_def_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
     fn select_sub_node_property_entries(&self, pointer: &str)
@@ -16,6 +18,10 @@ pub trait Selectors {
         &self,
         pointer: &str,
     ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
     fn select_sub_node_prefix_items_entries(
         &self,
         pointer: &str,
@@ -24,6 +30,28 @@ pub trait Selectors {
     fn select_sub_node_all_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
     fn select_sub_node_any_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
     fn select_sub_node_one_of_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_contains_entries(&self, pointer: &str)
+        -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_dependent_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_property_names_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_if_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_then_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_else_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_unevaluated_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
+    fn select_sub_node_unevaluated_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>>;
 }
 
 impl Selectors for Rc<ValueRc> {
@@ -39,20 +67,16 @@ impl Selectors for Rc<ValueRc> {
         self.as_object()?.get("$ref")?.as_str()
     }
 
-    fn select_all_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
-        let result = self.select_sub_nodes(pointer);
-        vec![
-            result.clone(),
-            result
-                .iter()
-                .flat_map(|(sub_pointer, sub_node)| {
-                    sub_node.select_all_sub_nodes(sub_pointer.as_str())
-                })
-                .collect(),
-        ]
-        .into_iter()
-        .flatten()
-        .collect()
+    fn select_dynamic_ref(&self) -> Option<&str> {
+        self.as_object()?.get("$dynamicRef")?.as_str()
+    }
+
+    fn select_anchor(&self) -> Option<&str> {
+        self.as_object()?.get("$anchor")?.as_str()
+    }
+
+    fn select_dynamic_anchor(&self) -> Option<&str> {
+        self.as_object()?.get("$dynamicAnchor")?.as_str()
     }
 
     fn select_sub_nodes(&self, pointer: &str) -> Vec<(String, Rc<ValueRc>)> {
@@ -63,6 +87,8 @@ impl Selectors for Rc<ValueRc> {
                 .unwrap_or_default(),
             self.select_sub_node_additional_properties_entries(pointer)
                 .unwrap_or_default(),
+            self.select_sub_node_pattern_properties_entries(pointer)
+                .unwrap_or_default(),
             self.select_sub_node_prefix_items_entries(pointer)
                 .unwrap_or_default(),
             self.select_sub_node_items_entries(pointer)
@@ -73,6 +99,23 @@ impl Selectors for Rc<ValueRc> {
                 .unwrap_or_default(),
             self.select_sub_node_one_of_entries(pointer)
                 .unwrap_or_default(),
+            self.select_sub_node_not_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_contains_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_dependent_schema_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_property_names_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_if_entries(pointer).unwrap_or_default(),
+            self.select_sub_node_then_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_else_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_unevaluated_properties_entries(pointer)
+                .unwrap_or_default(),
+            self.select_sub_node_unevaluated_items_entries(pointer)
+                .unwrap_or_default(),
         ]
         .into_iter()
         .flatten()
@@ -90,7 +133,7 @@ impl Selectors for Rc<ValueRc> {
             .iter()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -110,7 +153,7 @@ impl Selectors for Rc<ValueRc> {
             .iter()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -125,10 +168,27 @@ impl Selectors for Rc<ValueRc> {
         let select_name = "additionalProperties";
         let selected = self.as_object()?.get(select_name)?;
 
-        let result = vec![(
-            join_json_pointer(vec![pointer, select_name]),
-            selected.clone(),
-        )];
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_pattern_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "patternProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
 
         Some(result)
     }
@@ -145,7 +205,7 @@ impl Selectors for Rc<ValueRc> {
             .enumerate()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.to_string().as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -157,10 +217,7 @@ impl Selectors for Rc<ValueRc> {
         let select_name = "items";
         let selected = self.as_object()?.get(select_name)?;
 
-        let result = vec![(
-            join_json_pointer(vec![pointer, select_name]),
-            selected.clone(),
-        )];
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
 
         Some(result)
     }
@@ -174,7 +231,7 @@ impl Selectors for Rc<ValueRc> {
             .enumerate()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.to_string().as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -192,7 +249,7 @@ impl Selectors for Rc<ValueRc> {
             .enumerate()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.to_string().as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -210,7 +267,46 @@ impl Selectors for Rc<ValueRc> {
             .enumerate()
             .map(|(sub_pointer, sub_node)| {
                 (
-                    join_json_pointer(vec![pointer, select_name, sub_pointer.to_string().as_str()]),
+                    join_json_pointer(pointer, &[select_name, sub_pointer.to_string().as_str()]),
+                    sub_node.clone(),
+                )
+            })
+            .collect();
+
+        Some(result)
+    }
+    fn select_sub_node_not_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "not";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_contains_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "contains";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_dependent_schema_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "dependentSchemas";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = selected
+            .as_object()?
+            .iter()
+            .map(|(sub_pointer, sub_node)| {
+                (
+                    join_json_pointer(pointer, &[select_name, sub_pointer.as_str()]),
                     sub_node.clone(),
                 )
             })
@@ -218,6 +314,63 @@ impl Selectors for Rc<ValueRc> {
 
         Some(result)
     }
+    fn select_sub_node_property_names_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "propertyNames";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_if_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "if";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_then_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "then";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_else_entries(&self, pointer: &str) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "else";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_unevaluated_properties_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "unevaluatedProperties";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
+    fn select_sub_node_unevaluated_items_entries(
+        &self,
+        pointer: &str,
+    ) -> Option<Vec<(String, Rc<ValueRc>)>> {
+        let select_name = "unevaluatedItems";
+        let selected = self.as_object()?.get(select_name)?;
+
+        let result = vec![(join_json_pointer(pointer, &[select_name]), selected.clone())];
+
+        Some(result)
+    }
 
     //
 }
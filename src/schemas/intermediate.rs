@@ -0,0 +1,1019 @@
+use crate::error::Error;
+use crate::utils::{
+    json_pointer::{join_json_pointer, json_pointer_from_fragment, json_pointer_to_fragment},
+    value_rc::{as_exact_integer, compare_numbers, format_value, json_equals, ValueRc},
+};
+use indexmap::IndexMap;
+use regex::Regex;
+use std::{cmp::Ordering, collections::HashSet, fmt::Display, rc::Rc};
+use url::Url;
+
+/// The json types a node may allow.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntermediateType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl IntermediateType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "null" => Some(Self::Null),
+            "boolean" => Some(Self::Boolean),
+            "integer" => Some(Self::Integer),
+            "number" => Some(Self::Number),
+            "string" => Some(Self::String),
+            "array" => Some(Self::Array),
+            "object" => Some(Self::Object),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+}
+
+impl Display for IntermediateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A node of a schema, the same for every draft. Sub nodes are referenced by
+/// their node url.
+#[derive(Debug, Clone)]
+pub struct IntermediateNode {
+    pub node_url: Url,
+    /// Set when the node is a boolean schema, that allows everything or
+    /// nothing.
+    pub boolean: Option<bool>,
+
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub examples: Vec<Rc<ValueRc>>,
+
+    /// None when every type is allowed.
+    pub types: Option<Vec<IntermediateType>>,
+    /// The node a `$ref` resolves to.
+    pub reference: Option<Url>,
+    /// The node a `$dynamicRef` or `$recursiveRef` resolves to when the
+    /// dynamic scope is not known, see [`Manager::get_dynamic_scope`].
+    ///
+    /// [`Manager::get_dynamic_scope`]: super::manager::Manager::get_dynamic_scope
+    pub dynamic_reference: Option<Url>,
+    pub enum_values: Option<Vec<Rc<ValueRc>>>,
+    pub const_value: Option<Rc<ValueRc>>,
+
+    pub properties: Vec<(String, Url)>,
+    pub pattern_properties: Vec<(String, Url)>,
+    pub additional_properties: Option<Url>,
+    pub property_names: Option<Url>,
+    pub required: Vec<String>,
+    /// The properties that are required when a property is present, from
+    /// `dependentRequired` or the array form of `dependencies`.
+    pub dependent_required: Vec<(String, Vec<String>)>,
+    /// The schemas that apply when a property is present, from
+    /// `dependentSchemas` or the schema form of `dependencies`.
+    pub dependent_schemas: Vec<(String, Url)>,
+    pub unevaluated_properties: Option<Url>,
+
+    /// The first items of an array, by position.
+    pub prefix_items: Vec<Url>,
+    /// The items after the prefix items.
+    pub items: Option<Url>,
+    pub contains: Option<Url>,
+    pub unevaluated_items: Option<Url>,
+
+    pub all_of: Vec<Url>,
+    pub any_of: Vec<Url>,
+    pub one_of: Vec<Url>,
+    pub not: Option<Url>,
+    pub if_: Option<Url>,
+    pub then: Option<Url>,
+    pub else_: Option<Url>,
+
+    pub constraints: IntermediateConstraints,
+}
+
+/// The assertions of a node that do not affect its type.
+#[derive(Debug, Clone, Default)]
+pub struct IntermediateConstraints {
+    pub multiple_of: Option<Rc<ValueRc>>,
+    pub minimum: Option<Rc<ValueRc>>,
+    pub exclusive_minimum: Option<Rc<ValueRc>>,
+    pub maximum: Option<Rc<ValueRc>>,
+    pub exclusive_maximum: Option<Rc<ValueRc>>,
+
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    pub pattern: Option<String>,
+
+    pub min_items: Option<u64>,
+    pub max_items: Option<u64>,
+    pub unique_items: bool,
+
+    pub min_properties: Option<u64>,
+    pub max_properties: Option<u64>,
+
+    pub min_contains: Option<u64>,
+    pub max_contains: Option<u64>,
+
+    pub format: Option<String>,
+}
+
+impl IntermediateNode {
+    /// A node without any keywords, that allows everything.
+    pub fn new(node_url: Url) -> Self {
+        Self {
+            node_url,
+            boolean: None,
+            title: None,
+            description: None,
+            examples: Vec::new(),
+            types: None,
+            reference: None,
+            dynamic_reference: None,
+            enum_values: None,
+            const_value: None,
+            properties: Vec::new(),
+            pattern_properties: Vec::new(),
+            additional_properties: None,
+            property_names: None,
+            required: Vec::new(),
+            dependent_required: Vec::new(),
+            dependent_schemas: Vec::new(),
+            unevaluated_properties: None,
+            prefix_items: Vec::new(),
+            items: None,
+            contains: None,
+            unevaluated_items: None,
+            all_of: Vec::new(),
+            any_of: Vec::new(),
+            one_of: Vec::new(),
+            not: None,
+            if_: None,
+            then: None,
+            else_: None,
+            constraints: IntermediateConstraints::default(),
+        }
+    }
+
+    /// The node the type of this node is, a `$ref` goes before a dynamic
+    /// reference.
+    pub fn select_reference(&self) -> Option<&Url> {
+        self.reference.as_ref().or(self.dynamic_reference.as_ref())
+    }
+
+    /// Whether the node only refers to one other node, the annotations aside.
+    /// A node with keywords next to its reference is the intersection of both,
+    /// like an allOf.
+    pub fn is_reference_only(&self) -> bool {
+        let constraints = &self.constraints;
+
+        self.reference.is_some() != self.dynamic_reference.is_some()
+            && self.boolean.is_none()
+            && self.types.is_none()
+            && self.enum_values.is_none()
+            && self.const_value.is_none()
+            && self.properties.is_empty()
+            && self.pattern_properties.is_empty()
+            && self.additional_properties.is_none()
+            && self.property_names.is_none()
+            && self.required.is_empty()
+            && self.dependent_required.is_empty()
+            && self.dependent_schemas.is_empty()
+            && self.unevaluated_properties.is_none()
+            && self.prefix_items.is_empty()
+            && self.items.is_none()
+            && self.contains.is_none()
+            && self.unevaluated_items.is_none()
+            && self.all_of.is_empty()
+            && self.any_of.is_empty()
+            && self.one_of.is_empty()
+            && self.not.is_none()
+            && self.if_.is_none()
+            && self.then.is_none()
+            && self.else_.is_none()
+            && constraints.multiple_of.is_none()
+            && constraints.minimum.is_none()
+            && constraints.exclusive_minimum.is_none()
+            && constraints.maximum.is_none()
+            && constraints.exclusive_maximum.is_none()
+            && constraints.min_length.is_none()
+            && constraints.max_length.is_none()
+            && constraints.pattern.is_none()
+            && constraints.min_items.is_none()
+            && constraints.max_items.is_none()
+            && !constraints.unique_items
+            && constraints.min_properties.is_none()
+            && constraints.max_properties.is_none()
+            && constraints.min_contains.is_none()
+            && constraints.max_contains.is_none()
+            && constraints.format.is_none()
+    }
+
+    /// The only type of the node, if it allows exactly one.
+    pub fn select_single_type(&self) -> Option<IntermediateType> {
+        match self.types.as_deref() {
+            Some([value]) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// The nodes with the members of their allOf merged into them, so a node with
+/// an allOf can be generated as a single type. A reference next to an allOf or
+/// next to other keywords is merged as one of the members, only a node that is
+/// just a reference stays one. Sub nodes that more than one member has, like
+/// a property with the same name, are merged into a new node at
+/// `<node>/allOf/<keyword>/...`. The additionalProperties of a member only
+/// apply to the properties the member does not have itself. A member with
+/// keywords that can not be merged, like a second anyOf, stays in the allOf of
+/// the merged node.
+pub fn merge_all_of(
+    nodes: &IndexMap<Url, IntermediateNode>,
+) -> Result<IndexMap<Url, IntermediateNode>, Error> {
+    let mut merger = AllOfMerger {
+        nodes: nodes.clone(),
+        merged: HashSet::new(),
+        merging: HashSet::new(),
+        created: HashSet::new(),
+    };
+
+    for node_url in nodes.keys() {
+        merger.merge_node(node_url)?;
+    }
+
+    Ok(merger.nodes)
+}
+
+struct AllOfMerger {
+    nodes: IndexMap<Url, IntermediateNode>,
+    merged: HashSet<Url>,
+    // an allOf that contains itself, through references, would never end
+    merging: HashSet<Url>,
+    // the nodes for sub nodes that are in more than one member
+    created: HashSet<Url>,
+}
+
+impl AllOfMerger {
+    fn merge_node(&mut self, node_url: &Url) -> Result<Option<IntermediateNode>, Error> {
+        if self.merging.contains(node_url) {
+            return Ok(None);
+        }
+
+        let Some(node) = self.nodes.get(node_url) else {
+            return Ok(None);
+        };
+        let has_members = !node.all_of.is_empty()
+            || (node.select_reference().is_some() && !node.is_reference_only());
+        if self.merged.contains(node_url) || !has_members {
+            return Ok(Some(node.clone()));
+        }
+
+        let mut result = node.clone();
+        let mut member_urls = std::mem::take(&mut result.all_of);
+        let reference_urls = [result.reference.take(), result.dynamic_reference.take()];
+        member_urls.splice(0..0, reference_urls.into_iter().flatten());
+
+        self.merging.insert(node_url.clone());
+        for member_url in &member_urls {
+            if let Some(member) = self.resolve_node(member_url)? {
+                result = self.intersect(result, &member)?;
+            }
+        }
+        self.merging.remove(node_url);
+
+        self.merged.insert(node_url.clone());
+        self.nodes.insert(node_url.clone(), result.clone());
+
+        Ok(Some(result))
+    }
+
+    // the merged node at the end of a chain of references
+    fn resolve_node(&mut self, node_url: &Url) -> Result<Option<IntermediateNode>, Error> {
+        let mut visited = vec![node_url.clone()];
+
+        loop {
+            let Some(node) = self.merge_node(visited.last().unwrap())? else {
+                return Ok(None);
+            };
+
+            match node.select_reference().cloned() {
+                Some(node_ref_url) if !visited.contains(&node_ref_url) => {
+                    visited.push(node_ref_url)
+                }
+                Some(_node_ref_url) => return Ok(None),
+                None => return Ok(Some(node)),
+            }
+        }
+    }
+
+    // Only what both nodes allow. The title, the description and the examples
+    // are those of the node that has the allOf, they do not apply to the
+    // members.
+    fn intersect(
+        &mut self,
+        mut left: IntermediateNode,
+        right: &IntermediateNode,
+    ) -> Result<IntermediateNode, Error> {
+        let node_url = left.node_url.clone();
+        let conflict = |keyword, left: String, right: String| Error::ConflictingAllOf {
+            node_url: node_url.clone(),
+            message: format!("{} {} and {}", keyword, left, right),
+        };
+
+        match right.boolean {
+            Some(true) => return Ok(left),
+            Some(false) => left.boolean = Some(false),
+            None => {}
+        }
+
+        left.types = match (left.types, &right.types) {
+            (Some(left_types), Some(right_types)) => {
+                let types = intersect_types(&left_types, right_types);
+                if types.is_empty() {
+                    return Err(conflict(
+                        "type",
+                        format_types(&left_types),
+                        format_types(right_types),
+                    ));
+                }
+                Some(types)
+            }
+            (left_types, right_types) => left_types.or_else(|| right_types.clone()),
+        };
+
+        left.const_value = match (left.const_value, &right.const_value) {
+            (Some(left_value), Some(right_value)) => {
+                if !json_equals(&left_value, right_value) {
+                    return Err(conflict(
+                        "const",
+                        format_value(&left_value),
+                        format_value(right_value),
+                    ));
+                }
+                Some(left_value)
+            }
+            (left_value, right_value) => left_value.or_else(|| right_value.clone()),
+        };
+
+        left.enum_values = match (left.enum_values, &right.enum_values) {
+            (Some(left_values), Some(right_values)) => {
+                let values: Vec<_> = left_values
+                    .iter()
+                    .filter(|left_value| {
+                        right_values
+                            .iter()
+                            .any(|right_value| json_equals(left_value, right_value))
+                    })
+                    .cloned()
+                    .collect();
+                if values.is_empty() {
+                    return Err(conflict(
+                        "enum",
+                        format_values(&left_values),
+                        format_values(right_values),
+                    ));
+                }
+                Some(values)
+            }
+            (left_values, right_values) => left_values.or_else(|| right_values.clone()),
+        };
+
+        // a property of one member is restricted by the pattern or additional
+        // properties of the other member, if the other does not have it
+        let left_names: Vec<_> = left
+            .properties
+            .iter()
+            .map(|(name, _url)| name.clone())
+            .collect();
+        for (name, right_url) in &right.properties {
+            match left_names.iter().position(|other| other == name) {
+                Some(index) => {
+                    let left_url = left.properties[index].1.clone();
+                    left.properties[index].1 = self.merge_sub_nodes(
+                        &node_url,
+                        &["properties", name],
+                        &left_url,
+                        right_url,
+                    )?;
+                }
+                None => {
+                    let mut property_url = right_url.clone();
+                    for other_url in self.select_other_property_urls(&left, name) {
+                        property_url = self.merge_sub_nodes(
+                            &node_url,
+                            &["properties", name],
+                            &property_url,
+                            &other_url,
+                        )?;
+                    }
+                    left.properties.push((name.clone(), property_url));
+                }
+            }
+        }
+        for (name, left_url) in left.properties.iter_mut() {
+            if right.properties.iter().any(|(other, _url)| other == name) {
+                continue;
+            }
+            for other_url in self.select_other_property_urls(right, name) {
+                *left_url =
+                    self.merge_sub_nodes(&node_url, &["properties", name], left_url, &other_url)?;
+            }
+        }
+        for (pattern, right_url) in &right.pattern_properties {
+            match left
+                .pattern_properties
+                .iter()
+                .position(|(other, _url)| other == pattern)
+            {
+                Some(index) => {
+                    let left_url = left.pattern_properties[index].1.clone();
+                    left.pattern_properties[index].1 = self.merge_sub_nodes(
+                        &node_url,
+                        &["patternProperties", pattern],
+                        &left_url,
+                        right_url,
+                    )?;
+                }
+                None => left
+                    .pattern_properties
+                    .push((pattern.clone(), right_url.clone())),
+            }
+        }
+        left.additional_properties = self.merge_optional_sub_nodes(
+            &node_url,
+            "additionalProperties",
+            left.additional_properties,
+            &right.additional_properties,
+        )?;
+        left.property_names = self.merge_optional_sub_nodes(
+            &node_url,
+            "propertyNames",
+            left.property_names,
+            &right.property_names,
+        )?;
+        for name in &right.required {
+            if !left.required.contains(name) {
+                left.required.push(name.clone());
+            }
+        }
+
+        for (index, right_url) in right.prefix_items.iter().enumerate() {
+            match left.prefix_items.get(index).cloned() {
+                Some(left_url) => {
+                    left.prefix_items[index] = self.merge_sub_nodes(
+                        &node_url,
+                        &["prefixItems", &index.to_string()],
+                        &left_url,
+                        right_url,
+                    )?;
+                }
+                None => left.prefix_items.push(right_url.clone()),
+            }
+        }
+        left.items = self.merge_optional_sub_nodes(&node_url, "items", left.items, &right.items)?;
+
+        for (name, right_names) in &right.dependent_required {
+            match left
+                .dependent_required
+                .iter_mut()
+                .find(|(other, _names)| other == name)
+            {
+                Some((_name, left_names)) => {
+                    for name in right_names {
+                        if !left_names.contains(name) {
+                            left_names.push(name.clone());
+                        }
+                    }
+                }
+                None => left
+                    .dependent_required
+                    .push((name.clone(), right_names.clone())),
+            }
+        }
+        for (name, right_url) in &right.dependent_schemas {
+            match left
+                .dependent_schemas
+                .iter()
+                .position(|(other, _url)| other == name)
+            {
+                Some(index) => {
+                    let left_url = left.dependent_schemas[index].1.clone();
+                    left.dependent_schemas[index].1 = self.merge_sub_nodes(
+                        &node_url,
+                        &["dependentSchemas", name],
+                        &left_url,
+                        right_url,
+                    )?;
+                }
+                None => left
+                    .dependent_schemas
+                    .push((name.clone(), right_url.clone())),
+            }
+        }
+
+        left.constraints = intersect_constraints(left.constraints, &right.constraints);
+
+        // A node has one set of alternatives, one not, one condition and one
+        // contains. If both have them, the right node stays a member of the
+        // allOf. The unevaluated keywords only see what the member evaluates,
+        // so a member with them stays in the allOf too.
+        let mut is_residual =
+            right.unevaluated_properties.is_some() || right.unevaluated_items.is_some();
+        if left.any_of.is_empty() {
+            left.any_of = right.any_of.clone();
+        } else if !right.any_of.is_empty() {
+            is_residual = true;
+        }
+        if left.one_of.is_empty() {
+            left.one_of = right.one_of.clone();
+        } else if !right.one_of.is_empty() {
+            is_residual = true;
+        }
+        match (&left.not, &right.not) {
+            (None, Some(_)) => left.not = right.not.clone(),
+            (Some(_), Some(_)) => is_residual = true,
+            _ => {}
+        }
+        match (&left.if_, &right.if_) {
+            (None, Some(_)) => {
+                left.if_ = right.if_.clone();
+                left.then = right.then.clone();
+                left.else_ = right.else_.clone();
+            }
+            (Some(_), Some(_)) => is_residual = true,
+            _ => {}
+        }
+        match (&left.contains, &right.contains) {
+            (None, Some(_)) => {
+                left.contains = right.contains.clone();
+                left.constraints.min_contains = right.constraints.min_contains;
+                left.constraints.max_contains = right.constraints.max_contains;
+            }
+            (Some(_), Some(_)) => is_residual = true,
+            _ => {}
+        }
+
+        for member_url in right
+            .all_of
+            .iter()
+            .chain(is_residual.then_some(&right.node_url))
+        {
+            if !left.all_of.contains(member_url) {
+                left.all_of.push(member_url.clone());
+            }
+        }
+
+        Ok(left)
+    }
+
+    // the sub nodes that apply to a property of another member, the matching
+    // pattern properties or else the additional properties
+    fn select_other_property_urls(&self, node: &IntermediateNode, name: &str) -> Vec<Url> {
+        let mut result: Vec<_> = node
+            .pattern_properties
+            .iter()
+            .filter(|(pattern, _url)| {
+                Regex::new(pattern).is_ok_and(|pattern| pattern.is_match(name))
+            })
+            .map(|(_pattern, url)| url.clone())
+            .collect();
+        if result.is_empty() {
+            result.extend(node.additional_properties.clone());
+        }
+
+        // a sub node that allows everything changes nothing
+        result.retain(|url| {
+            self.nodes
+                .get(url)
+                .is_none_or(|sub_node| sub_node.boolean != Some(true))
+        });
+        result
+    }
+
+    fn merge_optional_sub_nodes(
+        &mut self,
+        parent_url: &Url,
+        keyword: &str,
+        left_url: Option<Url>,
+        right_url: &Option<Url>,
+    ) -> Result<Option<Url>, Error> {
+        match (left_url, right_url) {
+            (Some(left_url), Some(right_url)) => Ok(Some(self.merge_sub_nodes(
+                parent_url,
+                &[keyword],
+                &left_url,
+                right_url,
+            )?)),
+            (left_url, right_url) => Ok(left_url.or_else(|| right_url.clone())),
+        }
+    }
+
+    // A new node with both sub nodes as its allOf, or the same node with the
+    // right one merged into it if it was created before.
+    fn merge_sub_nodes(
+        &mut self,
+        parent_url: &Url,
+        tokens: &[&str],
+        left_url: &Url,
+        right_url: &Url,
+    ) -> Result<Url, Error> {
+        if left_url == right_url {
+            return Ok(left_url.clone());
+        }
+
+        if self.created.contains(left_url) {
+            let left = self.nodes.get(left_url).unwrap().clone();
+            if let Some(right) = self.resolve_node(right_url)? {
+                let node = self.intersect(left, &right)?;
+                self.nodes.insert(left_url.clone(), node);
+            }
+            return Ok(left_url.clone());
+        }
+
+        let pointer = json_pointer_from_fragment(parent_url.fragment().unwrap_or_default());
+        let pointer = join_json_pointer(&pointer, &[&["allOf"], tokens].concat());
+        let mut node_url = parent_url.clone();
+        node_url.set_fragment(Some(json_pointer_to_fragment(&pointer).as_str()));
+
+        let mut node = IntermediateNode::new(node_url.clone());
+        node.all_of = vec![left_url.clone(), right_url.clone()];
+        self.nodes.insert(node_url.clone(), node);
+        self.created.insert(node_url.clone());
+        self.merge_node(&node_url)?;
+
+        Ok(node_url)
+    }
+}
+
+// an integer is a number too
+fn intersect_types(left: &[IntermediateType], right: &[IntermediateType]) -> Vec<IntermediateType> {
+    let mut result: Vec<_> = left
+        .iter()
+        .filter_map(|value| match value {
+            value if right.contains(value) => Some(*value),
+            IntermediateType::Number if right.contains(&IntermediateType::Integer) => {
+                Some(IntermediateType::Integer)
+            }
+            IntermediateType::Integer if right.contains(&IntermediateType::Number) => {
+                Some(IntermediateType::Integer)
+            }
+            _ => None,
+        })
+        .collect();
+    result.sort();
+    result.dedup();
+    result
+}
+
+// The strictest of both. Two patterns or formats can not be combined into
+// one, and a multiple of two decimal fractions is not known, the first one is
+// kept.
+fn intersect_constraints(
+    left: IntermediateConstraints,
+    right: &IntermediateConstraints,
+) -> IntermediateConstraints {
+    IntermediateConstraints {
+        multiple_of: match (left.multiple_of, &right.multiple_of) {
+            (Some(left_value), Some(right_value)) => Some(
+                least_common_multiple(&left_value, right_value)
+                    .map(Rc::new)
+                    .unwrap_or(left_value),
+            ),
+            (left_value, right_value) => left_value.or_else(|| right_value.clone()),
+        },
+        minimum: select_number(left.minimum, &right.minimum, Ordering::Greater),
+        exclusive_minimum: select_number(
+            left.exclusive_minimum,
+            &right.exclusive_minimum,
+            Ordering::Greater,
+        ),
+        maximum: select_number(left.maximum, &right.maximum, Ordering::Less),
+        exclusive_maximum: select_number(
+            left.exclusive_maximum,
+            &right.exclusive_maximum,
+            Ordering::Less,
+        ),
+
+        min_length: left.min_length.max(right.min_length),
+        max_length: select_minimum(left.max_length, right.max_length),
+        pattern: left.pattern.or_else(|| right.pattern.clone()),
+
+        min_items: left.min_items.max(right.min_items),
+        max_items: select_minimum(left.max_items, right.max_items),
+        unique_items: left.unique_items || right.unique_items,
+
+        min_properties: left.min_properties.max(right.min_properties),
+        max_properties: select_minimum(left.max_properties, right.max_properties),
+
+        // these belong to contains, they are not merged
+        min_contains: left.min_contains,
+        max_contains: left.max_contains,
+
+        format: left.format.or_else(|| right.format.clone()),
+    }
+}
+
+// the number that is further in the direction of ordering
+fn select_number(
+    left: Option<Rc<ValueRc>>,
+    right: &Option<Rc<ValueRc>>,
+    ordering: Ordering,
+) -> Option<Rc<ValueRc>> {
+    match (left, right) {
+        (Some(left_value), Some(right_value)) => {
+            if compare_numbers(right_value, &left_value) == Some(ordering) {
+                Some(right_value.clone())
+            } else {
+                Some(left_value)
+            }
+        }
+        (left_value, right_value) => left_value.or_else(|| right_value.clone()),
+    }
+}
+
+// None is no maximum, not the smallest
+fn select_minimum(left: Option<u64>, right: Option<u64>) -> Option<u64> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(left.min(right)),
+        (left, right) => left.or(right),
+    }
+}
+
+fn least_common_multiple(left: &ValueRc, right: &ValueRc) -> Option<ValueRc> {
+    let left = as_exact_integer(left)?;
+    let right = as_exact_integer(right)?;
+    if left <= 0 || right <= 0 {
+        return None;
+    }
+
+    let (mut a, mut b) = (left, right);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    let value = left / a * right;
+    Some(match i64::try_from(value) {
+        Ok(value) => ValueRc::Integer(value),
+        Err(_error) => ValueRc::UnsignedInteger(value.try_into().ok()?),
+    })
+}
+
+fn format_types(types: &[IntermediateType]) -> String {
+    match types {
+        [value] => format!("\"{}\"", value),
+        types => format!(
+            "[{}]",
+            types
+                .iter()
+                .map(|value| format!("\"{}\"", value))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn format_values(values: &[Rc<ValueRc>]) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|value| format_value(value))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+
+    fn merge(node_url: &Url, node: &str) -> IndexMap<Url, IntermediateNode> {
+        let node: ValueRc = serde_json::from_str(node).unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        manager.get_merged_intermediate_nodes().unwrap()
+    }
+
+    #[test]
+    fn merge_all_of_nodes() {
+        let node: ValueRc = serde_json::from_str(
+            r##"
+            {
+                "allOf": [
+                    { "$ref": "#/$defs/base" },
+                    {
+                        "required": ["name"],
+                        "properties": {
+                            "id": { "type": "integer", "maximum": 5 },
+                            "name": { "type": "string", "minLength": 1 }
+                        }
+                    }
+                ],
+                "$defs": {
+                    "base": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": { "type": "number", "maximum": 10 }
+                        }
+                    }
+                }
+            }
+            "##,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/named".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let nodes = manager.get_merged_intermediate_nodes().unwrap();
+
+        let node = nodes.get(&node_url).unwrap();
+        assert!(node.all_of.is_empty());
+        assert_eq!(node.select_single_type(), Some(IntermediateType::Object));
+        assert_eq!(node.required, vec!["id".to_owned(), "name".to_owned()]);
+        assert_eq!(
+            node.properties,
+            vec![
+                (
+                    "id".to_owned(),
+                    node_url.join("#/allOf/properties/id").unwrap()
+                ),
+                (
+                    "name".to_owned(),
+                    node_url.join("#/allOf/1/properties/name").unwrap()
+                ),
+            ]
+        );
+
+        let id_node = nodes.get(&node.properties[0].1).unwrap();
+        assert_eq!(
+            id_node.select_single_type(),
+            Some(IntermediateType::Integer)
+        );
+        assert_eq!(
+            id_node
+                .constraints
+                .maximum
+                .as_ref()
+                .and_then(|value| value.as_integer()),
+            Some(5)
+        );
+
+        let node: ValueRc = serde_json::from_str(
+            r#"
+            {
+                "allOf": [{ "type": "string" }, { "type": "integer" }]
+            }
+            "#,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/conflicting".parse().unwrap();
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let error = manager.get_merged_intermediate_nodes().unwrap_err();
+        assert_eq!(
+            error.root_error().to_string(),
+            r#"conflicting allOf members in https://example.com/conflicting: type "string" and "integer""#
+        );
+    }
+
+    #[test]
+    fn merge_all_of_annotations() {
+        let node_url: Url = "https://example.com/annotated".parse().unwrap();
+        let nodes = merge(
+            &node_url,
+            r#"
+            {
+                "allOf": [{ "title": "Member", "description": "A member" }]
+            }
+            "#,
+        );
+
+        let node = nodes.get(&node_url).unwrap();
+        assert_eq!(node.title, None);
+        assert_eq!(node.description, None);
+    }
+
+    #[test]
+    fn merge_all_of_applicators() {
+        let node_url: Url = "https://example.com/applicators".parse().unwrap();
+        let nodes = merge(
+            &node_url,
+            r#"
+            {
+                "allOf": [
+                    { "anyOf": [{ "type": "string" }, { "type": "integer" }] },
+                    { "anyOf": [{ "minLength": 1 }, { "minimum": 1 }] },
+                    { "not": { "const": "a" } },
+                    { "contains": { "type": "string" }, "minContains": 2 },
+                    { "unevaluatedProperties": false }
+                ]
+            }
+            "#,
+        );
+
+        let node = nodes.get(&node_url).unwrap();
+        assert_eq!(
+            node.any_of,
+            vec![
+                node_url.join("#/allOf/0/anyOf/0").unwrap(),
+                node_url.join("#/allOf/0/anyOf/1").unwrap(),
+            ]
+        );
+        assert_eq!(node.not, Some(node_url.join("#/allOf/2/not").unwrap()));
+        assert_eq!(
+            node.contains,
+            Some(node_url.join("#/allOf/3/contains").unwrap())
+        );
+        assert_eq!(node.constraints.min_contains, Some(2));
+        assert_eq!(node.unevaluated_properties, None);
+        assert_eq!(
+            node.all_of,
+            vec![
+                node_url.join("#/allOf/1").unwrap(),
+                node_url.join("#/allOf/4").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_all_of_additional_properties() {
+        let node_url: Url = "https://example.com/closed".parse().unwrap();
+        let nodes = merge(
+            &node_url,
+            r#"
+            {
+                "allOf": [
+                    {
+                        "properties": { "name": { "type": "string" } },
+                        "patternProperties": { "^x-": { "type": "integer" } },
+                        "additionalProperties": false
+                    },
+                    {
+                        "properties": {
+                            "x-id": true,
+                            "bark": { "type": "boolean" }
+                        },
+                        "additionalProperties": { "minLength": 1 }
+                    }
+                ]
+            }
+            "#,
+        );
+
+        let node = nodes.get(&node_url).unwrap();
+        let property = |name: &str| {
+            let (_name, url) = node
+                .properties
+                .iter()
+                .find(|(other, _url)| other == name)
+                .unwrap();
+            nodes.get(url).unwrap()
+        };
+
+        // the additional properties of the second member apply to name
+        assert_eq!(
+            property("name").select_single_type(),
+            Some(IntermediateType::String)
+        );
+        assert_eq!(property("name").constraints.min_length, Some(1));
+
+        // the pattern properties of the first member apply to x-id
+        assert_eq!(
+            property("x-id").select_single_type(),
+            Some(IntermediateType::Integer)
+        );
+
+        // the first member does not allow bark
+        assert_eq!(property("bark").boolean, Some(false));
+    }
+}
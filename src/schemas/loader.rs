@@ -1,26 +1,305 @@
+use super::intermediate::{IntermediateConstraints, IntermediateNode, IntermediateType};
+use crate::error::Error;
+use crate::utils::json_pointer::{join_json_pointer, json_pointer_from_fragment};
 use crate::utils::value_rc::ValueRc;
 use std::rc::Rc;
 use url::Url;
 
 pub type LoaderBox<'a> = Box<dyn Loader + 'a>;
 
+/// Reads the nodes of one draft of json schema. Every url passed to a loader
+/// is the url of a node, a root node url has no fragment.
 pub trait Loader {
     fn is_schema_root_node(&self, node: Rc<ValueRc>) -> bool;
 
-    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), &'static str>;
+    /// Stores a document, the node urls of its sub nodes are known after
+    /// index_root_node.
+    fn load_root_node(&mut self, node: Rc<ValueRc>, node_url: &Url) -> Result<(), Error>;
 
-    fn index_root_node(&mut self, node_url: &Url) -> Result<Vec<Url>, &'static str>;
+    /// Indexes the nodes of a loaded document, returns their urls.
+    fn index_root_node(&mut self, node_url: &Url) -> Result<Vec<Url>, Error>;
 
+    /// Pairs of node url and retrieval url of the documents referenced from
+    /// the node.
     fn get_sub_node_urls(
         &self,
         node: Rc<ValueRc>,
         node_url: &Url,
         retrieval_url: &Url,
-    ) -> Result<Vec<(Url, Url)>, &'static str>;
+    ) -> Result<Vec<(Url, Url)>, Error>;
 
-    fn get_root_node_url(
+    fn get_root_node_url(&self, node: Rc<ValueRc>, default_node_url: &Url) -> Result<Url, Error>;
+
+    fn select_node(&self, node_url: &Url) -> Option<Rc<ValueRc>>;
+
+    /// The node in the representation that is the same for every draft, see
+    /// [`read_intermediate_node`].
+    fn select_intermediate_node(&self, node_url: &Url) -> Option<IntermediateNode>;
+
+    /// Where the `$dynamicRef` or `$recursiveRef` of a node points to, given
+    /// the resources in the dynamic scope, outermost first.
+    fn select_node_dynamic_ref_url(&self, node_url: &Url, dynamic_scope: &[Url]) -> Option<Url>;
+
+    fn resolve_anchor_node_url(&self, anchor_node_url: &Url) -> Option<Url>;
+}
+
+/// Reads the keywords of a node into an intermediate node. Only the keywords
+/// of the draft of the node are read, get_sub_node_url gives the url of the
+/// sub node at a pointer. References are not resolved, only the manager knows
+/// where they point to.
+pub fn read_intermediate_node(
+    node_url: &Url,
+    node: &Rc<ValueRc>,
+    keywords: &[&str],
+    get_sub_node_url: impl Fn(&str, &Rc<ValueRc>) -> Option<Url>,
+) -> IntermediateNode {
+    let reader = KeywordReader {
+        node_url,
+        pointer: json_pointer_from_fragment(node_url.fragment().unwrap_or_default()),
+        node,
+        keywords,
+        get_sub_node_url: &get_sub_node_url,
+    };
+
+    let mut result = IntermediateNode::new(node_url.clone());
+    result.boolean = node.as_bool();
+
+    result.title = reader.select_string("title");
+    result.description = reader.select_string("description");
+    result.examples = reader
+        .select("examples")
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default();
+
+    result.types = reader.select_strings("type").map(|types| {
+        types
+            .iter()
+            .filter_map(|value| IntermediateType::parse(value))
+            .collect()
+    });
+    result.reference = reader.select_url("$ref");
+    result.dynamic_reference = reader
+        .select_url("$dynamicRef")
+        .or_else(|| reader.select_url("$recursiveRef"));
+    result.enum_values = reader
+        .select("enum")
+        .and_then(|value| value.as_array().cloned());
+    result.const_value = reader.select("const").cloned();
+
+    result.properties = reader.select_sub_node_map("properties");
+    result.pattern_properties = reader.select_sub_node_map("patternProperties");
+    result.additional_properties = reader.select_sub_node("additionalProperties");
+    result.property_names = reader.select_sub_node("propertyNames");
+    result.required = reader.select_strings("required").unwrap_or_default();
+    result.unevaluated_properties = reader.select_sub_node("unevaluatedProperties");
+
+    // before 2019-09 dependencies had both the array and the schema form
+    result.dependent_required = reader.select_string_lists("dependentRequired");
+    result
+        .dependent_required
+        .extend(reader.select_string_lists("dependencies"));
+    result.dependent_schemas = reader.select_sub_node_map("dependentSchemas");
+    result
+        .dependent_schemas
+        .extend(reader.select_sub_node_map("dependencies"));
+
+    // before 2020-12 the prefix items were the array form of items, followed
+    // by additionalItems
+    if reader
+        .select("items")
+        .and_then(|value| value.as_array())
+        .is_some()
+    {
+        result.prefix_items = reader.select_sub_node_list("items");
+        result.items = reader.select_sub_node("additionalItems");
+    } else {
+        result.prefix_items = reader.select_sub_node_list("prefixItems");
+        result.items = reader.select_sub_node("items");
+    }
+    result.contains = reader.select_sub_node("contains");
+    result.unevaluated_items = reader.select_sub_node("unevaluatedItems");
+
+    result.all_of = reader.select_sub_node_list("allOf");
+    result.any_of = reader.select_sub_node_list("anyOf");
+    result.one_of = reader.select_sub_node_list("oneOf");
+    result.not = reader.select_sub_node("not");
+    result.if_ = reader.select_sub_node("if");
+    result.then = reader.select_sub_node("then");
+    result.else_ = reader.select_sub_node("else");
+
+    let (minimum, exclusive_minimum) = reader.select_bound("minimum", "exclusiveMinimum");
+    let (maximum, exclusive_maximum) = reader.select_bound("maximum", "exclusiveMaximum");
+    result.constraints = IntermediateConstraints {
+        multiple_of: reader.select("multipleOf").cloned(),
+        minimum,
+        exclusive_minimum,
+        maximum,
+        exclusive_maximum,
+
+        min_length: reader.select_unsigned_integer("minLength"),
+        max_length: reader.select_unsigned_integer("maxLength"),
+        pattern: reader.select_string("pattern"),
+
+        min_items: reader.select_unsigned_integer("minItems"),
+        max_items: reader.select_unsigned_integer("maxItems"),
+        unique_items: reader
+            .select("uniqueItems")
+            .and_then(|value| value.as_bool())
+            .unwrap_or_default(),
+
+        min_properties: reader.select_unsigned_integer("minProperties"),
+        max_properties: reader.select_unsigned_integer("maxProperties"),
+
+        min_contains: reader.select_unsigned_integer("minContains"),
+        max_contains: reader.select_unsigned_integer("maxContains"),
+
+        format: reader.select_string("format"),
+    };
+
+    result
+}
+
+/// The keywords to read of a node before draft 2019-09. In those drafts a
+/// `$ref` replaces the node, the keywords next to it are ignored.
+pub fn select_reference_keywords<'k>(node: &ValueRc, keywords: &'k [&'k str]) -> &'k [&'k str] {
+    let has_reference = node
+        .as_object()
+        .and_then(|node| node.get("$ref"))
+        .is_some_and(|value| value.as_str().is_some());
+
+    if has_reference {
+        &["$ref"]
+    } else {
+        keywords
+    }
+}
+
+// reads the keywords of one node, keywords that are not in the draft are not
+// there
+struct KeywordReader<'r> {
+    node_url: &'r Url,
+    pointer: String,
+    node: &'r Rc<ValueRc>,
+    keywords: &'r [&'r str],
+    get_sub_node_url: &'r dyn Fn(&str, &Rc<ValueRc>) -> Option<Url>,
+}
+
+impl<'r> KeywordReader<'r> {
+    fn select(&self, keyword: &str) -> Option<&'r Rc<ValueRc>> {
+        if !self.keywords.contains(&keyword) {
+            return None;
+        }
+
+        self.node.as_object()?.get(keyword)
+    }
+
+    fn select_string(&self, keyword: &str) -> Option<String> {
+        self.select(keyword)?.as_str().map(|value| value.to_owned())
+    }
+
+    fn select_unsigned_integer(&self, keyword: &str) -> Option<u64> {
+        self.select(keyword)?.as_unsigned_integer()
+    }
+
+    // a single string is a list of one
+    fn select_strings(&self, keyword: &str) -> Option<Vec<String>> {
+        let selected = self.select(keyword)?;
+
+        if let Some(value) = selected.as_str() {
+            return Some(vec![value.to_owned()]);
+        }
+
+        selected
+            .as_array()?
+            .iter()
+            .map(|value| value.as_str().map(|value| value.to_owned()))
+            .collect()
+    }
+
+    // the members of an object that are lists of strings
+    fn select_string_lists(&self, keyword: &str) -> Vec<(String, Vec<String>)> {
+        let Some(selected) = self.select(keyword).and_then(|value| value.as_object()) else {
+            return Vec::new();
+        };
+
+        selected
+            .iter()
+            .filter_map(|(name, value)| {
+                let values = value
+                    .as_array()?
+                    .iter()
+                    .map(|value| value.as_str().map(|value| value.to_owned()))
+                    .collect::<Option<_>>()?;
+                Some((name.clone(), values))
+            })
+            .collect()
+    }
+
+    fn select_url(&self, keyword: &str) -> Option<Url> {
+        self.node_url.join(self.select(keyword)?.as_str()?).ok()
+    }
+
+    // in draft-04 the exclusive keywords are flags that make the bound
+    // exclusive, after that they are bounds of their own
+    fn select_bound(
         &self,
-        node: Rc<ValueRc>,
-        default_node_url: &Url,
-    ) -> Result<Url, &'static str>;
+        keyword: &str,
+        exclusive_keyword: &str,
+    ) -> (Option<Rc<ValueRc>>, Option<Rc<ValueRc>>) {
+        let bound = self.select(keyword).cloned();
+        let exclusive_bound = self.select(exclusive_keyword);
+
+        match exclusive_bound.and_then(|value| value.as_bool()) {
+            Some(true) => (None, bound),
+            Some(false) => (bound, None),
+            None => (bound, exclusive_bound.cloned()),
+        }
+    }
+
+    fn select_sub_node(&self, keyword: &str) -> Option<Url> {
+        let sub_node = self.select(keyword)?;
+        if !is_schema(sub_node) {
+            return None;
+        }
+
+        (self.get_sub_node_url)(&join_json_pointer(&self.pointer, &[keyword]), sub_node)
+    }
+
+    fn select_sub_node_list(&self, keyword: &str) -> Vec<Url> {
+        let Some(selected) = self.select(keyword).and_then(|value| value.as_array()) else {
+            return Vec::new();
+        };
+
+        selected
+            .iter()
+            .enumerate()
+            .filter(|(_index, sub_node)| is_schema(sub_node))
+            .filter_map(|(index, sub_node)| {
+                let index = index.to_string();
+                let sub_pointer = join_json_pointer(&self.pointer, &[keyword, index.as_str()]);
+                (self.get_sub_node_url)(&sub_pointer, sub_node)
+            })
+            .collect()
+    }
+
+    // the members of an object that are schemas, by name
+    fn select_sub_node_map(&self, keyword: &str) -> Vec<(String, Url)> {
+        let Some(selected) = self.select(keyword).and_then(|value| value.as_object()) else {
+            return Vec::new();
+        };
+
+        selected
+            .iter()
+            .filter(|(_name, sub_node)| is_schema(sub_node))
+            .filter_map(|(name, sub_node)| {
+                let sub_pointer = join_json_pointer(&self.pointer, &[keyword, name.as_str()]);
+                let sub_node_url = (self.get_sub_node_url)(&sub_pointer, sub_node)?;
+                Some((name.clone(), sub_node_url))
+            })
+            .collect()
+    }
+}
+
+fn is_schema(node: &ValueRc) -> bool {
+    node.as_object().is_some() || node.as_bool().is_some()
 }
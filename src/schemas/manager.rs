@@ -1,17 +1,52 @@
-use super::{loader::LoaderBox, meta::MetaSchemaId};
-use crate::{schemas, utils::value_rc::ValueRc};
-use std::{collections::HashMap, fs::File, rc::Rc};
+use super::{
+    intermediate::{merge_all_of, IntermediateNode},
+    loader::LoaderBox,
+    meta::{self, MetaSchemaId},
+};
+use crate::{
+    error::{join_url, Error},
+    schemas,
+    utils::{
+        json_pointer::{json_pointer_from_fragment, resolve_json_pointer},
+        source_location::{index_source_locations, SourceLocation, SourceLocationMap},
+        value_rc::ValueRc,
+    },
+};
+use indexmap::IndexMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    rc::Rc,
+};
 use url::Url;
 
+#[cfg(feature = "http")]
+const HTTP_REDIRECTS: u32 = 5;
+#[cfg(feature = "http")]
+const HTTP_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+#[cfg(feature = "http")]
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Loads schema documents and everything they reference, and answers questions
+/// about the loaded nodes no matter what draft they are written in.
 #[derive(Default)]
 pub struct Manager<'a> {
     loaders: HashMap<MetaSchemaId, LoaderBox<'a>>,
     retrieval_root_node_map: HashMap<Url, Url>,
     root_node_retrieval_map: HashMap<Url, Url>,
     root_node_meta_schema_id_map: HashMap<Url, MetaSchemaId>,
+    alias_root_node_map: HashMap<Url, Url>,
+    root_node_referrer_map: HashMap<Url, Url>,
+    // in the order the nodes were loaded
+    node_meta_schema_id_map: IndexMap<Url, MetaSchemaId>,
+    retrieval_url_mappings: Vec<(String, Url)>,
+    source_location_map: SourceLocationMap,
+    // every url a source was fetched from, in order
+    fetched_urls: Vec<Url>,
 }
 
 impl<'a> Manager<'a> {
+    /// A manager with a loader for every supported draft.
     pub fn new() -> Self {
         Self {
             loaders: vec![
@@ -43,42 +78,128 @@ impl<'a> Manager<'a> {
         }
     }
 
+    /// Retrieval urls that start with url_prefix are fetched from
+    /// mapped_url_prefix instead. The retrieval url itself is kept, so relative
+    /// references still resolve against the original location.
+    pub fn add_retrieval_url_mapping(&mut self, url_prefix: &str, mapped_url_prefix: &Url) {
+        self.retrieval_url_mappings
+            .push((url_prefix.to_owned(), mapped_url_prefix.clone()));
+    }
+
+    /// Loads retrieval url mappings from a json file, an object with url
+    /// prefixes as keys and the mapped url prefixes as values. Mapped url
+    /// prefixes may be relative to the file.
+    pub fn load_retrieval_url_mappings(&mut self, url: &Url) -> Result<(), Error> {
+        let mappings = self.fetch_json_from_url(url)?;
+
+        for (url_prefix, mapped_url_prefix) in mappings
+            .as_object()
+            .ok_or_else(|| Error::InvalidRetrievalUrlMappings { url: url.clone() })?
+        {
+            let mapped_url_prefix = mapped_url_prefix
+                .as_str()
+                .ok_or_else(|| Error::InvalidRetrievalUrlMappings { url: url.clone() })?;
+            let mapped_url_prefix = join_url(url, mapped_url_prefix)?;
+
+            self.add_retrieval_url_mapping(url_prefix, &mapped_url_prefix);
+        }
+
+        Ok(())
+    }
+
+    /// Loads an already deserialized document without fetching what it
+    /// references.
     pub fn load_root_node(
         &mut self,
         node: Rc<ValueRc>,
         node_url: &Url,
         default_meta_schema_id: MetaSchemaId,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), Error> {
         let meta_schema_id = self.discover_meta_schema_id(node.clone(), default_meta_schema_id);
 
         let loader = self.loaders.get_mut(&meta_schema_id).unwrap();
 
         let node_url = loader.get_root_node_url(node.clone(), node_url)?;
 
+        self.root_node_meta_schema_id_map
+            .insert(node_url.clone(), meta_schema_id);
+
         loader.load_root_node(node, &node_url)?;
-        loader.index_root_node(&node_url)?;
+        for sub_node_url in loader.index_root_node(&node_url)? {
+            // sub nodes without a fragment are embedded resources
+            if sub_node_url.fragment().is_none() && sub_node_url != node_url {
+                self.root_node_meta_schema_id_map
+                    .insert(sub_node_url.clone(), meta_schema_id);
+                if let Some(retrieval_url) = self.root_node_retrieval_map.get(&node_url) {
+                    self.root_node_retrieval_map
+                        .insert(sub_node_url.clone(), retrieval_url.clone());
+                }
+                self.root_node_referrer_map
+                    .entry(sub_node_url.clone())
+                    .or_insert_with(|| node_url.clone());
+            }
+
+            self.node_meta_schema_id_map
+                .insert(sub_node_url, meta_schema_id);
+        }
 
         Ok(())
     }
 
+    /// Fetches the document at retrieval_url and loads it as node_url, together
+    /// with every document it references. Documents without a `$schema` are
+    /// loaded as default_meta_schema_id.
     pub fn load_from_url(
         &mut self,
         node_url: &Url,
         retrieval_url: &Url,
         default_meta_schema_id: MetaSchemaId,
-    ) -> Result<(), &'static str> {
-        if self.retrieval_root_node_map.contains_key(retrieval_url) {
+    ) -> Result<(), Error> {
+        let mut node_url = node_url.clone();
+        node_url.set_fragment(None);
+
+        if self.root_node_meta_schema_id_map.contains_key(&node_url) {
             return Ok(());
         }
 
-        let root_node = Self::fetch_json_from_url(retrieval_url)?;
+        if let Some(root_node_url) = self.retrieval_root_node_map.get(retrieval_url) {
+            if *root_node_url != node_url {
+                self.alias_root_node_map
+                    .insert(node_url, root_node_url.clone());
+            }
+            return Ok(());
+        }
+
+        // bundled documents are only loaded when referenced, they are not part
+        // of the output otherwise
+        let (source_url, source) = match meta::get_bundled_document(retrieval_url) {
+            Some(document) => (retrieval_url.clone(), document.to_owned()),
+            None => {
+                let source_url = self.map_retrieval_url(retrieval_url)?;
+                let source = self.fetch_source_from_url(&source_url)?;
+                (source_url, source)
+            }
+        };
+        let root_node = Self::deserialize_source(&source_url, &source)?;
+        index_source_locations(
+            &source_url,
+            &source,
+            &root_node,
+            &mut self.source_location_map,
+        );
 
         let meta_schema_id =
             self.discover_meta_schema_id(root_node.clone(), default_meta_schema_id);
 
         let loader = self.loaders.get(&meta_schema_id).unwrap();
 
-        let node_url = loader.get_root_node_url(root_node.clone(), node_url)?;
+        let alias_node_url = node_url;
+        let node_url = loader.get_root_node_url(root_node.clone(), &alias_node_url)?;
+
+        if alias_node_url != node_url {
+            self.alias_root_node_map
+                .insert(alias_node_url, node_url.clone());
+        }
 
         self.retrieval_root_node_map
             .insert(retrieval_url.clone(), node_url.clone());
@@ -87,17 +208,255 @@ impl<'a> Manager<'a> {
         self.root_node_meta_schema_id_map
             .insert(node_url.clone(), meta_schema_id);
 
-        for (sub_node_url, sub_retrieval_url) in
-            loader.get_sub_node_urls(root_node.clone(), &node_url, retrieval_url)?
+        // the root node is loaded first so errors about its nodes can be located
+        self.load_root_node(root_node.clone(), &node_url, default_meta_schema_id)?;
+
+        let loader = self.loaders.get(&meta_schema_id).unwrap();
+        for (sub_node_url, sub_retrieval_url) in loader
+            .get_sub_node_urls(root_node, &node_url, retrieval_url)
+            .map_err(|error| self.locate_error(error))?
         {
-            self.load_from_url(&sub_node_url, &sub_retrieval_url, meta_schema_id)?;
-        }
+            self.load_from_url(&sub_node_url, &sub_retrieval_url, meta_schema_id)
+                .map_err(|error| Error::Reference {
+                    referrer_url: node_url.clone(),
+                    source: Box::new(error),
+                })?;
 
-        self.load_root_node(root_node, &node_url, default_meta_schema_id)?;
+            // the sub node may be an already loaded (embedded) resource that
+            // was never retrieved by itself
+            let sub_root_node_url = match self.retrieval_root_node_map.get(&sub_retrieval_url) {
+                Some(sub_root_node_url) => sub_root_node_url.clone(),
+                None => {
+                    let mut sub_root_node_url = sub_node_url.clone();
+                    sub_root_node_url.set_fragment(None);
+                    sub_root_node_url
+                }
+            };
+            if sub_root_node_url != node_url {
+                self.root_node_referrer_map
+                    .entry(sub_root_node_url)
+                    .or_insert_with(|| node_url.clone());
+            }
+        }
 
         Ok(())
     }
 
+    /// The urls of all loaded schema nodes, in the order they were loaded.
+    pub fn get_node_urls(&self) -> impl Iterator<Item = &Url> {
+        self.node_meta_schema_id_map.keys()
+    }
+
+    /// The url of the loaded node that node_url refers to, following aliases and
+    /// anchors.
+    pub fn resolve_node_url(&self, node_url: &Url) -> Option<Url> {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+
+        let mut resolved_node_url = self
+            .alias_root_node_map
+            .get(&root_node_url)
+            .unwrap_or(&root_node_url)
+            .clone();
+        match node_url.fragment() {
+            None | Some("") => {}
+            Some(fragment) => resolved_node_url.set_fragment(Some(fragment)),
+        };
+
+        if self
+            .node_meta_schema_id_map
+            .contains_key(&resolved_node_url)
+        {
+            return Some(resolved_node_url);
+        }
+
+        let mut resolved_root_node_url = resolved_node_url.clone();
+        resolved_root_node_url.set_fragment(None);
+        let meta_schema_id = self
+            .root_node_meta_schema_id_map
+            .get(&resolved_root_node_url)?;
+        let loader = self.loaders.get(meta_schema_id)?;
+
+        loader.resolve_anchor_node_url(&resolved_node_url)
+    }
+
+    /// Every loaded node in the representation that is the same for every
+    /// draft, in the order they were loaded, with the references resolved.
+    /// Fails when a reference does not resolve to a loaded node.
+    pub fn get_intermediate_nodes(&self) -> Result<IndexMap<Url, IntermediateNode>, Error> {
+        self.get_node_urls()
+            .filter_map(|node_url| {
+                self.select_intermediate_node(node_url)
+                    .transpose()
+                    .map(|node| Ok((node_url.clone(), node?)))
+            })
+            .collect()
+    }
+
+    /// The intermediate nodes with the members of every allOf merged into the
+    /// node, see [`merge_all_of`].
+    pub fn get_merged_intermediate_nodes(&self) -> Result<IndexMap<Url, IntermediateNode>, Error> {
+        merge_all_of(&self.get_intermediate_nodes()?).map_err(|error| self.locate_error(error))
+    }
+
+    /// A node in the representation that is the same for every draft, with
+    /// its references resolved. None if the node is not loaded, fails when a
+    /// reference does not resolve to a loaded node.
+    pub fn select_intermediate_node(
+        &self,
+        node_url: &Url,
+    ) -> Result<Option<IntermediateNode>, Error> {
+        let Some(mut node) = self
+            .get_node_loader(node_url)
+            .and_then(|loader| loader.select_intermediate_node(node_url))
+        else {
+            return Ok(None);
+        };
+
+        let unresolved = |reference: &Url| {
+            self.locate_error(Error::UnresolvedReference {
+                node_url: node_url.clone(),
+                reference: reference.to_string(),
+            })
+        };
+        if let Some(node_ref_url) = &node.reference {
+            node.reference = Some(
+                self.resolve_node_url(node_ref_url)
+                    .ok_or_else(|| unresolved(node_ref_url))?,
+            );
+        }
+        if let Some(node_ref_url) = &node.dynamic_reference {
+            node.dynamic_reference = Some(
+                self.select_node_dynamic_ref_url(node_url, &self.get_dynamic_scope(node_url))
+                    .ok_or_else(|| unresolved(node_ref_url))?,
+            );
+        }
+
+        Ok(Some(node))
+    }
+
+    /// The keywords of the loaded nodes that are not keywords of their draft,
+    /// as located errors. Unknown keywords are allowed, so these are warnings
+    /// about what may be a misspelled keyword.
+    pub fn get_unknown_keywords(&self) -> Vec<Error> {
+        let mut result = Vec::new();
+
+        // an embedded resource is loaded by its own url too
+        let mut visited = HashSet::new();
+        for (node_url, meta_schema_id) in &self.node_meta_schema_id_map {
+            let Some(node) = self
+                .get_node_loader(node_url)
+                .and_then(|loader| loader.select_node(node_url))
+            else {
+                continue;
+            };
+            if !visited.insert(Rc::as_ptr(&node)) {
+                continue;
+            }
+
+            for keyword in node.as_object().into_iter().flat_map(|node| node.keys()) {
+                if !meta::is_known_keyword(*meta_schema_id, keyword) {
+                    result.push(self.locate_error(Error::UnknownKeyword {
+                        node_url: node_url.clone(),
+                        keyword: keyword.clone(),
+                    }));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Where the `$dynamicRef` or `$recursiveRef` of a node resolves to when
+    /// it is evaluated with the resources of a dynamic scope, outermost first.
+    pub fn select_node_dynamic_ref_url(
+        &self,
+        node_url: &Url,
+        dynamic_scope: &[Url],
+    ) -> Option<Url> {
+        let node_ref_url = self
+            .get_node_loader(node_url)?
+            .select_node_dynamic_ref_url(node_url, dynamic_scope)?;
+
+        self.resolve_node_url(&node_ref_url)
+    }
+
+    /// The meta-schema of the schema a node is part of.
+    pub fn get_node_meta_schema_id(&self, node_url: &Url) -> Option<MetaSchemaId> {
+        self.node_meta_schema_id_map.get(node_url).copied()
+    }
+
+    /// The dynamic scope of a node when it is not evaluated, approximated by
+    /// the chain of resources through which its resource was first
+    /// referenced, outermost first. The validator uses the resources it
+    /// actually passed through instead.
+    pub fn get_dynamic_scope(&self, node_url: &Url) -> Vec<Url> {
+        let mut root_node_url = node_url.clone();
+        root_node_url.set_fragment(None);
+
+        let mut result = vec![root_node_url.clone()];
+        while let Some(referrer_node_url) = self.root_node_referrer_map.get(&root_node_url) {
+            if result.contains(referrer_node_url) {
+                break;
+            }
+
+            result.push(referrer_node_url.clone());
+            root_node_url = referrer_node_url.clone();
+        }
+
+        result.reverse();
+        result
+    }
+
+    /// The urls of every document and mapping file that was fetched, bundled
+    /// meta-schemas are not fetched.
+    pub fn get_fetched_urls(&self) -> impl Iterator<Item = &Url> {
+        self.fetched_urls.iter()
+    }
+
+    /// Where the node is in its source document.
+    pub fn select_node_location(&self, node_url: &Url) -> Option<&SourceLocation> {
+        let node = match self.resolve_node_url(node_url) {
+            Some(node_url) => self.get_node_loader(&node_url)?.select_node(&node_url)?,
+            // not a schema node, but it may still point into a loaded document
+            None => {
+                let mut root_node_url = node_url.clone();
+                root_node_url.set_fragment(None);
+                let root_node_url = self.resolve_node_url(&root_node_url)?;
+                let root_node = self
+                    .get_node_loader(&root_node_url)?
+                    .select_node(&root_node_url)?;
+
+                let pointer = json_pointer_from_fragment(node_url.fragment()?);
+                resolve_json_pointer(&root_node, pointer.as_str())?
+            }
+        };
+
+        self.source_location_map.get(&Rc::as_ptr(&node))
+    }
+
+    // adds the source location of the node the error is about, when known
+    fn locate_error(&self, error: Error) -> Error {
+        let location = error
+            .node_url()
+            .and_then(|node_url| self.select_node_location(node_url))
+            .cloned();
+
+        match location {
+            Some(location) => Error::Located {
+                location: Box::new(location),
+                source: Box::new(error),
+            },
+            None => error,
+        }
+    }
+
+    fn get_node_loader(&self, node_url: &Url) -> Option<&LoaderBox<'a>> {
+        let meta_schema_id = self.node_meta_schema_id_map.get(node_url)?;
+
+        self.loaders.get(meta_schema_id)
+    }
+
     fn discover_meta_schema_id(
         &self,
         node: Rc<ValueRc>,
@@ -112,29 +471,508 @@ impl<'a> Manager<'a> {
         default_meta_schema_id
     }
 
-    fn fetch_json_from_url(url: &Url) -> Result<Rc<ValueRc>, &'static str> {
+    // the longest matching prefix wins
+    fn map_retrieval_url(&self, retrieval_url: &Url) -> Result<Url, Error> {
+        let mapping = self
+            .retrieval_url_mappings
+            .iter()
+            .filter(|(url_prefix, _mapped_url_prefix)| {
+                retrieval_url.as_str().starts_with(url_prefix.as_str())
+            })
+            .max_by_key(|(url_prefix, _mapped_url_prefix)| url_prefix.len());
+
+        let Some((url_prefix, mapped_url_prefix)) = mapping else {
+            return Ok(retrieval_url.clone());
+        };
+
+        let reference = &retrieval_url.as_str()[url_prefix.len()..];
+        format!("{}{}", mapped_url_prefix, reference)
+            .parse()
+            .map_err(|source| Error::InvalidUrl {
+                base_url: mapped_url_prefix.clone(),
+                reference: reference.to_owned(),
+                source,
+            })
+    }
+
+    fn fetch_json_from_url(&mut self, url: &Url) -> Result<Rc<ValueRc>, Error> {
+        let source = self.fetch_source_from_url(url)?;
+
+        Self::deserialize_source(url, &source)
+    }
+
+    fn deserialize_source(url: &Url, source: &str) -> Result<Rc<ValueRc>, Error> {
+        let value: ValueRc = serde_json::from_str(source).map_err(|source| Error::Deserialize {
+            url: url.clone(),
+            source,
+        })?;
+        let value = Rc::new(value);
+
+        Ok(value)
+    }
+
+    // the url is recorded even if fetching fails, a missing file may be
+    // created later
+    fn fetch_source_from_url(&mut self, url: &Url) -> Result<String, Error> {
+        if !self.fetched_urls.contains(url) {
+            self.fetched_urls.push(url.clone());
+        }
+
         match url.scheme() {
             "file" => {
-                let path = url.path();
-                let reader = File::open(path).or(Err("error reading file"))?;
+                let path = url
+                    .to_file_path()
+                    .map_err(|()| Error::InvalidFilePath { url: url.clone() })?;
+                let source = fs::read_to_string(path).map_err(|source| Error::ReadFile {
+                    url: url.clone(),
+                    source,
+                })?;
+
+                Ok(source)
+            }
+            #[cfg(feature = "http")]
+            "http" | "https" => {
+                let response = ureq::AgentBuilder::new()
+                    .redirects(HTTP_REDIRECTS)
+                    .timeout_connect(HTTP_CONNECT_TIMEOUT)
+                    .timeout(HTTP_TIMEOUT)
+                    .build()
+                    .get(url.as_str())
+                    .set("Accept", "application/schema+json, application/json")
+                    .call()
+                    .map_err(|source| Error::Fetch {
+                        url: url.clone(),
+                        source: Box::new(source),
+                    })?;
 
-                let value: ValueRc =
-                    serde_json::from_reader(reader).or(Err("error deserializing file content"))?;
-                let value = Rc::new(value);
+                if !Self::is_json_content_type(response.content_type()) {
+                    return Err(Error::UnexpectedContentType {
+                        url: url.clone(),
+                        content_type: response.content_type().to_owned(),
+                    });
+                }
 
-                Ok(value)
+                let source = response.into_string().map_err(|source| Error::ReadFile {
+                    url: url.clone(),
+                    source,
+                })?;
+
+                Ok(source)
             }
-            _ => Err("not supported"),
+            _ => Err(Error::UnsupportedScheme { url: url.clone() }),
         }
     }
+
+    // a lot of servers (like raw file hosts) serve schemas as plain text, so
+    // that is accepted too. Anything else, like an html error page, is not.
+    #[cfg(feature = "http")]
+    fn is_json_content_type(content_type: &str) -> bool {
+        let content_type = content_type.trim().to_ascii_lowercase();
+
+        content_type == "application/json"
+            || content_type == "text/plain"
+            || (content_type.starts_with("application/") && content_type.ends_with("+json"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schemas::intermediate::IntermediateType;
+    use crate::utils::test_directory::TestDirectory;
+    use std::path::Path;
 
     #[test]
     fn simple_manager() {
         let _manager = Manager::new();
     }
+
+    #[test]
+    fn node_urls_in_schema_order() {
+        let node: ValueRc = serde_json::from_str(
+            r#"
+            {
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "properties": {
+                    "b": { "type": "string" },
+                    "a": { "type": "string" },
+                    "c": { "type": "string" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/order".parse().unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let node_urls: Vec<_> = manager.get_node_urls().map(|url| url.as_str()).collect();
+        assert_eq!(
+            node_urls,
+            vec![
+                "https://example.com/order",
+                "https://example.com/order#/properties/b",
+                "https://example.com/order#/properties/a",
+                "https://example.com/order#/properties/c",
+            ]
+        );
+    }
+
+    #[test]
+    fn intermediate_nodes_of_drafts() {
+        let sources = [
+            (
+                MetaSchemaId::Draft04,
+                r##"
+                {
+                    "type": "array",
+                    "items": [{ "$ref": "#/definitions/id" }],
+                    "additionalItems": { "type": "string" },
+                    "definitions": {
+                        "id": { "type": "integer", "maximum": 10, "exclusiveMaximum": true }
+                    }
+                }
+                "##,
+                "#/items/0",
+                "#/definitions/id",
+            ),
+            (
+                MetaSchemaId::Draft202012,
+                r##"
+                {
+                    "type": "array",
+                    "prefixItems": [{ "$ref": "#/$defs/id" }],
+                    "items": { "type": "string" },
+                    "$defs": {
+                        "id": { "type": "integer", "exclusiveMaximum": 10 }
+                    }
+                }
+                "##,
+                "#/prefixItems/0",
+                "#/$defs/id",
+            ),
+        ];
+
+        for (meta_schema_id, source, prefix_item_fragment, id_fragment) in sources {
+            let node: ValueRc = serde_json::from_str(source).unwrap();
+            let node_url: Url = "https://example.com/tuple".parse().unwrap();
+
+            let mut manager = Manager::new();
+            manager
+                .load_root_node(Rc::new(node), &node_url, meta_schema_id)
+                .unwrap();
+
+            let nodes = manager.get_intermediate_nodes().unwrap();
+
+            let node = nodes.get(&node_url).unwrap();
+            assert_eq!(node.select_single_type(), Some(IntermediateType::Array));
+            assert_eq!(
+                node.prefix_items,
+                vec![node_url.join(prefix_item_fragment).unwrap()]
+            );
+
+            let item = nodes.get(&node.prefix_items[0]).unwrap();
+            let id_url = node_url.join(id_fragment).unwrap();
+            assert_eq!(item.reference.as_ref(), Some(&id_url));
+
+            let id = nodes.get(&id_url).unwrap();
+            assert_eq!(id.types, Some(vec![IntermediateType::Integer]));
+            assert_eq!(
+                id.constraints
+                    .exclusive_maximum
+                    .as_ref()
+                    .and_then(|value| value.as_integer()),
+                Some(10)
+            );
+            assert!(id.constraints.maximum.is_none());
+
+            let items = nodes.get(node.items.as_ref().unwrap()).unwrap();
+            assert_eq!(items.select_single_type(), Some(IntermediateType::String));
+        }
+    }
+
+    #[test]
+    fn load_bundled_meta_schemas() {
+        for meta_schema_id in [
+            MetaSchemaId::Draft202012,
+            MetaSchemaId::Draft201909,
+            MetaSchemaId::Draft07,
+            MetaSchemaId::Draft06,
+            MetaSchemaId::Draft04,
+        ] {
+            let root_node_url: Url = meta_schema_id.to_string().parse().unwrap();
+
+            let mut manager = Manager::new();
+            manager
+                .load_from_url(&root_node_url, &root_node_url, meta_schema_id)
+                .unwrap();
+
+            assert!(manager.get_node_urls().count() > 0);
+        }
+    }
+
+    #[test]
+    fn load_from_mapped_url() {
+        let fixtures_url =
+            Url::from_directory_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"))
+                .unwrap();
+        let retrieval_url: Url = "https://jns42-generator.org/schemas/array-with-ref.json"
+            .parse()
+            .unwrap();
+
+        let mut manager = Manager::new();
+        manager.add_retrieval_url_mapping("https://jns42-generator.org/schemas/", &fixtures_url);
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let root_node_url: Url = "https://jns42-generator.org/array-with-ref"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/items").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some("https://jns42-generator.org/simple-object".parse().unwrap())
+        );
+        assert_eq!(
+            manager.get_fetched_urls().cloned().collect::<Vec<_>>(),
+            vec![
+                fixtures_url.join("array-with-ref.json").unwrap(),
+                fixtures_url.join("simple-object.json").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_retrieval_url_mappings() {
+        let mappings_url = Url::from_file_path(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/retrieval-url-mappings.json"),
+        )
+        .unwrap();
+        let retrieval_url: Url = "https://jns42-generator.org/schemas/simple-object.json"
+            .parse()
+            .unwrap();
+
+        let mut manager = Manager::new();
+        manager.load_retrieval_url_mappings(&mappings_url).unwrap();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let root_node_url: Url = "https://jns42-generator.org/simple-object".parse().unwrap();
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url)
+                .unwrap()
+                .unwrap()
+                .description,
+            Some("A very simple object".to_owned())
+        );
+    }
+
+    #[test]
+    fn load_from_file_path_with_special_characters() {
+        let test_directory = TestDirectory::new("load_from_file_path_with_special_characters");
+        let directory_path = test_directory.path().join("jns42 schemäs");
+        fs::create_dir_all(&directory_path).unwrap();
+        let file_path = directory_path.join("simple object.json");
+        fs::write(
+            &file_path,
+            r#"{ "$schema": "https://json-schema.org/draft/2020-12/schema", "type": "object" }"#,
+        )
+        .unwrap();
+        let retrieval_url = Url::from_file_path(&file_path).unwrap();
+        assert!(retrieval_url.path().contains("%20"));
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        assert!(manager
+            .select_intermediate_node(&retrieval_url)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn locate_invalid_reference() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/invalid-reference.json");
+        let retrieval_url = Url::from_file_path(&fixture_path).unwrap();
+
+        let mut manager = Manager::new();
+        let error = manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("{}:5:10: invalid reference http://[invalid in https://jns42-generator.org/invalid-reference#/properties/a", fixture_path.display())
+        );
+
+        let root_node_url: Url = "https://jns42-generator.org/invalid-reference"
+            .parse()
+            .unwrap();
+        let location = manager.select_node_location(&root_node_url).unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+
+        let location = manager
+            .select_node_location(&root_node_url.join("#/properties").unwrap())
+            .unwrap();
+        assert_eq!((location.line, location.column), (4, 17));
+    }
+
+    #[test]
+    fn locate_unresolved_reference_and_unknown_keyword() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/unresolved-reference.json");
+        let retrieval_url = Url::from_file_path(&fixture_path).unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&retrieval_url, &retrieval_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let error = manager.get_intermediate_nodes().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("{}:5:10: unresolved reference https://jns42-generator.org/unresolved-reference#/$defs/missing in https://jns42-generator.org/unresolved-reference#/properties/a", fixture_path.display())
+        );
+
+        let warnings: Vec<_> = manager
+            .get_unknown_keywords()
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![format!("{}:5:10: unknown keyword tpye in https://jns42-generator.org/unresolved-reference#/properties/a", fixture_path.display())]
+        );
+    }
+
+    // serves canned responses, keyed by path, for every connection until the
+    // test process exits
+    #[cfg(feature = "http")]
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> Url {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url: Url = format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let responses: HashMap<_, _> = responses.into_iter().collect();
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut header_line = String::new();
+                    reader.read_line(&mut header_line).unwrap();
+                    if header_line.trim().is_empty() {
+                        break;
+                    }
+                }
+
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let response = responses
+                    .get(path)
+                    .copied()
+                    .unwrap_or("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        base_url
+    }
+
+    #[cfg(feature = "http")]
+    fn json_response(content_type: &str, body: &str) -> &'static str {
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        );
+        Box::leak(response.into_boxed_str())
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn load_from_http_url() {
+        let base_url = serve(vec![
+            (
+                "/schema",
+                json_response(
+                    "application/schema+json",
+                    r#"{
+                        "$schema": "https://json-schema.org/draft/2020-12/schema",
+                        "properties": { "a": { "$ref": "moved" } }
+                    }"#,
+                ),
+            ),
+            (
+                "/moved",
+                "HTTP/1.1 301 Moved Permanently\r\nLocation: /target\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            ),
+            (
+                "/target",
+                json_response(
+                    "application/json; charset=utf-8",
+                    r#"{ "$schema": "https://json-schema.org/draft/2020-12/schema", "type": "string" }"#,
+                ),
+            ),
+        ]);
+        let root_node_url = base_url.join("schema").unwrap();
+        let moved_node_url = base_url.join("moved").unwrap();
+
+        let mut manager = Manager::new();
+        manager
+            .load_from_url(&root_node_url, &root_node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .select_intermediate_node(&root_node_url.join("#/properties/a").unwrap())
+                .unwrap()
+                .unwrap()
+                .select_reference()
+                .cloned(),
+            Some(moved_node_url.clone())
+        );
+        assert_eq!(
+            manager
+                .select_intermediate_node(&moved_node_url)
+                .unwrap()
+                .unwrap()
+                .types,
+            Some(vec![IntermediateType::String])
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn load_from_http_url_with_unexpected_content_type() {
+        let base_url = serve(vec![(
+            "/schema",
+            json_response("text/html", "<html></html>"),
+        )]);
+        let root_node_url = base_url.join("schema").unwrap();
+
+        let mut manager = Manager::new();
+        assert!(matches!(
+            manager.load_from_url(&root_node_url, &root_node_url, MetaSchemaId::Draft202012),
+            Err(Error::UnexpectedContentType { .. })
+        ));
+    }
 }
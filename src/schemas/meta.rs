@@ -1,7 +1,9 @@
 use crate::schemas;
 use clap::ValueEnum;
 use std::fmt::Display;
+use url::Url;
 
+/// The drafts of json schema, by the id of their meta-schema.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
 pub enum MetaSchemaId {
     Unknown,
@@ -53,3 +55,52 @@ impl From<&str> for MetaSchemaId {
         }
     }
 }
+
+// The official meta-schemas are bundled so references to them resolve without
+// fetching anything.
+pub fn get_bundled_document(retrieval_url: &Url) -> Option<&'static str> {
+    let mut retrieval_url = retrieval_url.clone();
+    retrieval_url.set_fragment(None);
+
+    [
+        schemas::draft_2020_12::meta::META_SCHEMA_DOCUMENTS,
+        schemas::draft_2019_09::meta::META_SCHEMA_DOCUMENTS,
+        schemas::draft_07::meta::META_SCHEMA_DOCUMENTS,
+        schemas::draft_06::meta::META_SCHEMA_DOCUMENTS,
+        schemas::draft_04::meta::META_SCHEMA_DOCUMENTS,
+    ]
+    .into_iter()
+    .flatten()
+    .find(|(document_url, _document)| *document_url == retrieval_url.as_str())
+    .map(|(_document_url, document)| *document)
+}
+
+// Whether a keyword is a keyword of the draft, also when the intermediate node
+// does not have it. Nothing is known of an unknown draft.
+pub fn is_known_keyword(meta_schema_id: MetaSchemaId, keyword: &str) -> bool {
+    let (keywords, other_keywords) = match meta_schema_id {
+        MetaSchemaId::Draft202012 => (
+            schemas::draft_2020_12::meta::KEYWORDS,
+            schemas::draft_2020_12::meta::OTHER_KEYWORDS,
+        ),
+        MetaSchemaId::Draft201909 => (
+            schemas::draft_2019_09::meta::KEYWORDS,
+            schemas::draft_2019_09::meta::OTHER_KEYWORDS,
+        ),
+        MetaSchemaId::Draft07 => (
+            schemas::draft_07::meta::KEYWORDS,
+            schemas::draft_07::meta::OTHER_KEYWORDS,
+        ),
+        MetaSchemaId::Draft06 => (
+            schemas::draft_06::meta::KEYWORDS,
+            schemas::draft_06::meta::OTHER_KEYWORDS,
+        ),
+        MetaSchemaId::Draft04 => (
+            schemas::draft_04::meta::KEYWORDS,
+            schemas::draft_04::meta::OTHER_KEYWORDS,
+        ),
+        MetaSchemaId::Unknown => return true,
+    };
+
+    keywords.contains(&keyword) || other_keywords.contains(&keyword)
+}
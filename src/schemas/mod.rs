@@ -4,6 +4,7 @@ pub mod draft_07;
 pub mod draft_2019_09;
 pub mod draft_2020_12;
 
+pub mod intermediate;
 pub mod loader;
 pub mod manager;
 pub mod meta;
@@ -1,7 +1,149 @@
-pub fn join_json_pointer(parts: Vec<&str>) -> String {
-    parts
-        .iter()
-        .map(|part| urlencoding::encode(part))
-        .collect::<Vec<_>>()
-        .join("/")
+use super::value_rc::ValueRc;
+use std::rc::Rc;
+
+// appends reference tokens to a pointer, the tokens are escaped
+pub fn join_json_pointer(pointer: &str, tokens: &[&str]) -> String {
+    let mut result = pointer.to_owned();
+
+    for token in tokens {
+        result.push('/');
+        result.push_str(escape_json_pointer_token(token).as_str());
+    }
+
+    result
+}
+
+// the unescaped reference tokens of a pointer, None if the pointer is invalid
+pub fn parse_json_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(unescape_json_pointer_token)
+        .collect()
+}
+
+pub fn escape_json_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+// None if the token contains a ~ that is not followed by 0 or 1
+pub fn unescape_json_pointer_token(token: &str) -> Option<String> {
+    let mut result = String::new();
+
+    let mut characters = token.chars();
+    while let Some(character) = characters.next() {
+        if character != '~' {
+            result.push(character);
+            continue;
+        }
+
+        match characters.next()? {
+            '0' => result.push('~'),
+            '1' => result.push('/'),
+            _ => return None,
+        }
+    }
+
+    Some(result)
+}
+
+// Percent encodes every character that is not allowed in a uri fragment, see
+// section 6 of RFC 6901.
+pub fn json_pointer_to_fragment(pointer: &str) -> String {
+    let mut result = String::new();
+
+    for character in pointer.chars() {
+        if character.is_ascii_alphanumeric() || "-._~!$&'()*+,;=:@/?".contains(character) {
+            result.push(character);
+            continue;
+        }
+
+        let mut buffer = [0; 4];
+        for byte in character.encode_utf8(&mut buffer).bytes() {
+            result.push_str(format!("%{:02X}", byte).as_str());
+        }
+    }
+
+    result
+}
+
+pub fn json_pointer_from_fragment(fragment: &str) -> String {
+    urlencoding::decode(fragment)
+        .map(|pointer| pointer.into_owned())
+        .unwrap_or_else(|_error| fragment.to_owned())
+}
+
+pub fn resolve_json_pointer(node: &Rc<ValueRc>, pointer: &str) -> Option<Rc<ValueRc>> {
+    let mut result = node.clone();
+
+    for token in parse_json_pointer(pointer)? {
+        result = match result.as_ref() {
+            ValueRc::Object(object) => object.get(token.as_str())?.clone(),
+            ValueRc::Array(array) => {
+                // leading zeros are not allowed
+                if token.len() > 1 && token.starts_with('0') {
+                    return None;
+                }
+                array.get(token.parse::<usize>().ok()?)?.clone()
+            }
+            _ => return None,
+        };
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_and_parse() {
+        let pointer = join_json_pointer("/properties", &["a/b", "~x", "ü"]);
+        assert_eq!(pointer, "/properties/a~1b/~0x/ü");
+        assert_eq!(
+            parse_json_pointer(pointer.as_str()),
+            Some(vec![
+                "properties".to_owned(),
+                "a/b".to_owned(),
+                "~x".to_owned(),
+                "ü".to_owned()
+            ])
+        );
+
+        assert_eq!(parse_json_pointer(""), Some(vec![]));
+        assert_eq!(parse_json_pointer("/"), Some(vec!["".to_owned()]));
+        assert_eq!(parse_json_pointer("a"), None);
+        assert_eq!(parse_json_pointer("/~2"), None);
+        assert_eq!(unescape_json_pointer_token("~01"), Some("~1".to_owned()));
+    }
+
+    #[test]
+    fn fragment() {
+        let pointer = "/$defs/^~1/a%b c/ü";
+        let fragment = json_pointer_to_fragment(pointer);
+
+        assert_eq!(fragment, "/$defs/%5E~1/a%25b%20c/%C3%BC");
+        assert_eq!(json_pointer_from_fragment(fragment.as_str()), pointer);
+    }
+
+    #[test]
+    fn resolve() {
+        let node: ValueRc =
+            serde_json::from_str(r#"{ "a/b": [0, { "~x": true }], "": 1 }"#).unwrap();
+        let node = Rc::new(node);
+
+        assert!(Rc::ptr_eq(&resolve_json_pointer(&node, "").unwrap(), &node));
+        assert_eq!(
+            resolve_json_pointer(&node, "/a~1b/1/~0x").and_then(|node| node.as_bool()),
+            Some(true)
+        );
+        assert!(resolve_json_pointer(&node, "/").is_some());
+        assert!(resolve_json_pointer(&node, "/a~1b/01").is_none());
+        assert!(resolve_json_pointer(&node, "/a~1b/2").is_none());
+    }
 }
@@ -1,2 +1,5 @@
 pub mod json_pointer;
+pub mod source_location;
+#[cfg(test)]
+pub mod test_directory;
 pub mod value_rc;
@@ -0,0 +1,224 @@
+use super::value_rc::ValueRc;
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+use url::Url;
+
+// Source locations are keyed by the address of the node, nodes are shared
+// between the loaders so that identifies them no matter what url they have.
+pub type SourceLocationMap = HashMap<*const ValueRc, SourceLocation>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub url: Url,
+    // one based
+    pub line: usize,
+    // one based, in characters
+    pub column: usize,
+    // the byte span of the node in the source
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.url.to_file_path() {
+            Ok(path) => write!(f, "{}:{}:{}", path.display(), self.line, self.column),
+            Err(_) => write!(f, "{}:{}:{}", self.url, self.line, self.column),
+        }
+    }
+}
+
+// serde_json does not expose positions to a visitor, so the source is scanned
+// once more next to the already deserialized node. The source is expected to
+// be valid json, as it was deserialized without errors.
+pub fn index_source_locations(
+    url: &Url,
+    source: &str,
+    node: &Rc<ValueRc>,
+    source_location_map: &mut SourceLocationMap,
+) {
+    let mut scanner = Scanner {
+        url,
+        source,
+        position: 0,
+        line: 1,
+        line_start: 0,
+        source_location_map,
+    };
+
+    scanner.scan_value(Some(node));
+}
+
+struct Scanner<'s> {
+    url: &'s Url,
+    source: &'s str,
+    position: usize,
+    line: usize,
+    line_start: usize,
+    source_location_map: &'s mut SourceLocationMap,
+}
+
+impl<'s> Scanner<'s> {
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'\n' => {
+                    self.position += 1;
+                    self.line += 1;
+                    self.line_start = self.position;
+                }
+                b' ' | b'\t' | b'\r' => self.position += 1,
+                _ => break,
+            }
+        }
+    }
+
+    // skips whitespace and the expected byte
+    fn skip_token(&mut self, token: u8) -> bool {
+        self.skip_whitespace();
+
+        if self.peek() == Some(token) {
+            self.position += 1;
+            return true;
+        }
+
+        false
+    }
+
+    // the node is None when there is nothing to register a location for, like
+    // a duplicate key
+    fn scan_value(&mut self, node: Option<&Rc<ValueRc>>) {
+        self.skip_whitespace();
+
+        let start = self.position;
+        let line = self.line;
+        let column = self.source[self.line_start..start].chars().count() + 1;
+
+        match self.peek() {
+            Some(b'{') => self.scan_object(node),
+            Some(b'[') => self.scan_array(node),
+            Some(b'"') => {
+                self.scan_string();
+            }
+            _ => {
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n') {
+                        break;
+                    }
+                    self.position += 1;
+                }
+            }
+        }
+
+        if let Some(node) = node {
+            self.source_location_map.insert(
+                Rc::as_ptr(node),
+                SourceLocation {
+                    url: self.url.clone(),
+                    line,
+                    column,
+                    start,
+                    end: self.position,
+                },
+            );
+        }
+    }
+
+    fn scan_object(&mut self, node: Option<&Rc<ValueRc>>) {
+        self.position += 1;
+
+        if self.skip_token(b'}') {
+            return;
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.scan_string();
+            self.skip_token(b':');
+
+            let sub_node = node
+                .and_then(|node| node.as_object())
+                .and_then(|object| object.get(&key));
+            self.scan_value(sub_node);
+
+            if !self.skip_token(b',') {
+                self.skip_token(b'}');
+                break;
+            }
+        }
+    }
+
+    fn scan_array(&mut self, node: Option<&Rc<ValueRc>>) {
+        self.position += 1;
+
+        if self.skip_token(b']') {
+            return;
+        }
+
+        for index in 0.. {
+            let sub_node = node
+                .and_then(|node| node.as_array())
+                .and_then(|array| array.get(index));
+            self.scan_value(sub_node);
+
+            if !self.skip_token(b',') {
+                self.skip_token(b']');
+                break;
+            }
+        }
+    }
+
+    fn scan_string(&mut self) -> String {
+        let start = self.position;
+        self.position += 1;
+
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            match byte {
+                b'\\' => self.position += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+
+        serde_json::from_str(&self.source[start..self.position]).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_nested_nodes() {
+        let source = "{\n  \"a\": [1, {\"b\\\"\": \"ü\"}],\n  \"c\": \"d\"\n}";
+        let url: Url = "file:///schema.json".parse().unwrap();
+        let node: Rc<ValueRc> = Rc::new(serde_json::from_str(source).unwrap());
+
+        let mut source_location_map = SourceLocationMap::new();
+        index_source_locations(&url, source, &node, &mut source_location_map);
+
+        let object = node.as_object().unwrap();
+        let array = object.get("a").unwrap().as_array().unwrap();
+        let inner_object = array.get(1).unwrap().as_object().unwrap();
+
+        let location = source_location_map.get(&Rc::as_ptr(&node)).unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+        assert_eq!((location.start, location.end), (0, source.len()));
+
+        let location = source_location_map
+            .get(&Rc::as_ptr(inner_object.get("b\"").unwrap()))
+            .unwrap();
+        assert_eq!((location.line, location.column), (2, 20));
+
+        let location = source_location_map
+            .get(&Rc::as_ptr(object.get("c").unwrap()))
+            .unwrap();
+        assert_eq!((location.line, location.column), (3, 8));
+        assert_eq!(&source[location.start..location.end], "\"d\"");
+        assert_eq!(location.to_string(), "/schema.json:3:8");
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+
+// A directory for the files of a single test, unique per test and process so
+// parallel test runs do not share it. It is removed when dropped.
+pub struct TestDirectory {
+    path: PathBuf,
+}
+
+impl TestDirectory {
+    pub fn new(test_name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "jns42-generator-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
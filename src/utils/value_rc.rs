@@ -1,35 +1,64 @@
 use core::fmt;
-use serde::de;
-use std::{collections::HashMap, rc::Rc};
+use indexmap::IndexMap;
+use serde::{de, ser};
+use std::{cmp::Ordering, rc::Rc};
 
 #[derive(Debug)]
 pub enum ValueRc {
     Null,
     Bool(bool),
+    // non negative integers are only unsigned when they do not fit an i64
+    Integer(i64),
+    UnsignedInteger(u64),
     Float(f64),
+    // with arbitrary precision, every number that is not an integer is kept
+    // as it was written
+    #[cfg(feature = "arbitrary-precision")]
+    Number(String),
     String(String),
     Array(Vec<Rc<ValueRc>>),
-    Object(HashMap<String, Rc<ValueRc>>),
+    // keeps the order of the source
+    Object(IndexMap<String, Rc<ValueRc>>),
 }
 
 impl ValueRc {
-    pub fn _as_null(&self) -> Option<()> {
+    pub fn as_null(&self) -> Option<()> {
         match self {
             ValueRc::Null => Some(()),
             _ => None,
         }
     }
 
-    pub fn _as_bool(&self) -> Option<bool> {
+    pub fn as_bool(&self) -> Option<bool> {
         match self {
             ValueRc::Bool(value) => Some(*value),
             _ => None,
         }
     }
 
-    pub fn _as_float(&self) -> Option<f64> {
+    pub fn as_integer(&self) -> Option<i64> {
         match self {
+            ValueRc::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_unsigned_integer(&self) -> Option<u64> {
+        match self {
+            ValueRc::Integer(value) => (*value).try_into().ok(),
+            ValueRc::UnsignedInteger(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // any number, possibly losing precision
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            ValueRc::Integer(value) => Some(*value as f64),
+            ValueRc::UnsignedInteger(value) => Some(*value as f64),
             ValueRc::Float(value) => Some(*value),
+            #[cfg(feature = "arbitrary-precision")]
+            ValueRc::Number(value) => value.parse().ok(),
             _ => None,
         }
     }
@@ -48,7 +77,7 @@ impl ValueRc {
         }
     }
 
-    pub fn as_object(&self) -> Option<&HashMap<String, Rc<ValueRc>>> {
+    pub fn as_object(&self) -> Option<&IndexMap<String, Rc<ValueRc>>> {
         match self {
             ValueRc::Object(value) => Some(value),
             _ => None,
@@ -56,6 +85,80 @@ impl ValueRc {
     }
 }
 
+pub(crate) fn as_exact_integer(value: &ValueRc) -> Option<i128> {
+    match value {
+        ValueRc::Integer(value) => Some(*value as i128),
+        ValueRc::UnsignedInteger(value) => Some(*value as i128),
+        _ => None,
+    }
+}
+
+// integers are compared exactly, other numbers as floats
+pub(crate) fn compare_numbers(left: &ValueRc, right: &ValueRc) -> Option<Ordering> {
+    if let (Some(left), Some(right)) = (as_exact_integer(left), as_exact_integer(right)) {
+        return Some(left.cmp(&right));
+    }
+
+    left.as_float()?.partial_cmp(&right.as_float()?)
+}
+
+// equal as json, so 1 and 1.0 are the same number
+pub(crate) fn json_equals(left: &ValueRc, right: &ValueRc) -> bool {
+    match (left, right) {
+        (ValueRc::Null, ValueRc::Null) => true,
+        (ValueRc::Bool(left), ValueRc::Bool(right)) => left == right,
+        (ValueRc::String(left), ValueRc::String(right)) => left == right,
+        (ValueRc::Array(left), ValueRc::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|(left, right)| json_equals(left, right))
+        }
+        (ValueRc::Object(left), ValueRc::Object(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(name, left)| {
+                    right
+                        .get(name)
+                        .is_some_and(|right| json_equals(left, right))
+                })
+        }
+        (left, right) => compare_numbers(left, right) == Some(Ordering::Equal),
+    }
+}
+
+// the value as compact json, for messages
+pub(crate) fn format_value(value: &ValueRc) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+impl ser::Serialize for ValueRc {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            ValueRc::Null => serializer.serialize_unit(),
+            ValueRc::Bool(value) => serializer.serialize_bool(*value),
+            ValueRc::Integer(value) => serializer.serialize_i64(*value),
+            ValueRc::UnsignedInteger(value) => serializer.serialize_u64(*value),
+            ValueRc::Float(value) => serializer.serialize_f64(*value),
+            // only serializes as a plain number with serde_json
+            #[cfg(feature = "arbitrary-precision")]
+            ValueRc::Number(value) => {
+                serde_json::Number::from_string_unchecked(value.clone()).serialize(serializer)
+            }
+            ValueRc::String(value) => serializer.serialize_str(value),
+            ValueRc::Array(value) => {
+                serializer.collect_seq(value.iter().map(|value| value.as_ref()))
+            }
+            ValueRc::Object(value) => {
+                serializer.collect_map(value.iter().map(|(key, value)| (key, value.as_ref())))
+            }
+        }
+    }
+}
+
 impl<'de> de::Deserialize<'de> for ValueRc {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -65,6 +168,29 @@ impl<'de> de::Deserialize<'de> for ValueRc {
     }
 }
 
+// recognised whether or not this crate enables the feature, another crate in
+// the build may enable arbitrary_precision of serde_json
+const ARBITRARY_PRECISION_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+fn parse_number<E>(value: String) -> Result<ValueRc, E>
+where
+    E: de::Error,
+{
+    if let Ok(result) = value.parse() {
+        return Ok(ValueRc::Integer(result));
+    }
+
+    if let Ok(result) = value.parse() {
+        return Ok(ValueRc::UnsignedInteger(result));
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    return Ok(ValueRc::Number(value));
+
+    #[cfg(not(feature = "arbitrary-precision"))]
+    value.parse().map(ValueRc::Float).map_err(E::custom)
+}
+
 struct ValueRcVisitor;
 
 impl<'de> de::Visitor<'de> for ValueRcVisitor {
@@ -93,16 +219,18 @@ impl<'de> de::Visitor<'de> for ValueRcVisitor {
     where
         E: de::Error,
     {
-        let result = value as f64;
-        Ok(ValueRc::Float(result))
+        let result = value;
+        Ok(ValueRc::Integer(result))
     }
 
     fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        let result = value as f64;
-        Ok(ValueRc::Float(result))
+        match i64::try_from(value) {
+            Ok(result) => Ok(ValueRc::Integer(result)),
+            Err(_) => Ok(ValueRc::UnsignedInteger(value)),
+        }
     }
 
     fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
@@ -136,8 +264,16 @@ impl<'de> de::Visitor<'de> for ValueRcVisitor {
     where
         A: de::MapAccess<'de>,
     {
-        let mut result = HashMap::new();
-        while let Some((key, value)) = value_map.next_entry::<String, ValueRc>()? {
+        let mut result = IndexMap::new();
+        while let Some(key) = value_map.next_key::<String>()? {
+            // serde_json passes arbitrary precision numbers as a map with a
+            // single, private, key
+            if key == ARBITRARY_PRECISION_NUMBER_TOKEN {
+                let value = value_map.next_value::<String>()?;
+                return parse_number(value);
+            }
+
+            let value = value_map.next_value::<ValueRc>()?;
             result.insert(key, Rc::new(value));
         }
         Ok(ValueRc::Object(result))
@@ -161,4 +297,57 @@ mod tests {
 
         println!("{:?}", a);
     }
+
+    #[test]
+    fn preserve_object_order() {
+        let source = r#"{"b":1,"a":2,"c":{"z":3,"y":4}}"#;
+        let value: ValueRc = serde_json::from_str(source).unwrap();
+
+        let keys: Vec<_> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["b", "a", "c"]);
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), source);
+    }
+
+    #[test]
+    fn preserve_numbers() {
+        let source = "[9007199254740993,18446744073709551615,-1,1,1.0,0.5]";
+        let value: ValueRc = serde_json::from_str(source).unwrap();
+
+        let array = value.as_array().unwrap();
+        assert_eq!(array[0].as_integer(), Some(9007199254740993));
+        assert_eq!(array[1].as_unsigned_integer(), Some(u64::MAX));
+        assert_eq!(array[2].as_integer(), Some(-1));
+        assert_eq!(array[3].as_integer(), Some(1));
+        assert_eq!(array[4].as_integer(), None);
+        assert_eq!(array[5].as_float(), Some(0.5));
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), source);
+    }
+
+    #[test]
+    fn read_arbitrary_precision_numbers() {
+        // what serde_json passes when any crate enables arbitrary_precision
+        let source = r#"[
+            {"$serde_json::private::Number":"1"},
+            {"$serde_json::private::Number":"18446744073709551615"},
+            {"$serde_json::private::Number":"0.5"}
+        ]"#;
+        let value: ValueRc = serde_json::from_str(source).unwrap();
+
+        let array = value.as_array().unwrap();
+        assert_eq!(array[0].as_integer(), Some(1));
+        assert_eq!(array[1].as_unsigned_integer(), Some(u64::MAX));
+        assert_eq!(array[2].as_float(), Some(0.5));
+        assert!(array[2].as_object().is_none());
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test]
+    fn preserve_arbitrary_precision_numbers() {
+        let source = "[123456789012345678901234567890,0.10000000000000000000000001,1e+400]";
+        let value: ValueRc = serde_json::from_str(source).unwrap();
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), source);
+    }
 }
@@ -0,0 +1,312 @@
+use crate::utils::value_rc::ValueRc;
+use regex::Regex;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use url::Url;
+
+/// Whether the instance is in a format, None if the format is not supported.
+/// The formats apply to strings only, every other instance is in the format.
+pub fn is_format(format: &str, instance: &ValueRc) -> Option<bool> {
+    let check: fn(&str) -> bool = match format {
+        "date-time" => is_date_time,
+        "date" => is_date,
+        "time" => is_time,
+        "duration" => is_duration,
+        "email" => |value| is_email(value, false),
+        "idn-email" => |value| is_email(value, true),
+        "hostname" => |value| is_hostname(value, false),
+        "idn-hostname" => |value| is_hostname(value, true),
+        "ipv4" => |value| value.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => |value| value.parse::<Ipv6Addr>().is_ok(),
+        "uri" | "iri" => is_uri,
+        "uri-reference" | "iri-reference" => is_uri_reference,
+        "uri-template" => is_uri_template,
+        "uuid" => is_uuid,
+        "regex" => |value| Regex::new(value).is_ok(),
+        "json-pointer" => is_json_pointer,
+        "relative-json-pointer" => is_relative_json_pointer,
+        _ => return None,
+    };
+
+    Some(instance.as_str().is_none_or(check))
+}
+
+// the digits of a fixed width number, like the month in a date
+fn parse_digits(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+// full-date of rfc 3339
+fn is_date(value: &str) -> bool {
+    let (Some(year), Some(month), Some(day)) = (
+        value.get(0..4).and_then(parse_digits),
+        value.get(5..7).and_then(parse_digits),
+        value.get(8..10).and_then(parse_digits),
+    ) else {
+        return false;
+    };
+    if value.len() != 10 || &value[4..5] != "-" || &value[7..8] != "-" {
+        return false;
+    }
+
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days).contains(&day)
+}
+
+// full-time of rfc 3339, the time and the offset
+fn is_time(value: &str) -> bool {
+    let (Some(hour), Some(minute), Some(second)) = (
+        value.get(0..2).and_then(parse_digits),
+        value.get(3..5).and_then(parse_digits),
+        value.get(6..8).and_then(parse_digits),
+    ) else {
+        return false;
+    };
+    if &value[2..3] != ":" || &value[5..6] != ":" {
+        return false;
+    }
+
+    let mut rest = &value[8..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let length = fraction
+            .bytes()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if length == 0 {
+            return false;
+        }
+        rest = &fraction[length..];
+    }
+
+    let offset_minutes = if rest.eq_ignore_ascii_case("z") {
+        0
+    } else {
+        let sign = match rest.get(0..1) {
+            Some("+") => 1,
+            Some("-") => -1,
+            _ => return false,
+        };
+        let (Some(offset_hour), Some(offset_minute)) = (
+            rest.get(1..3).and_then(parse_digits),
+            rest.get(4..6).and_then(parse_digits),
+        ) else {
+            return false;
+        };
+        if rest.len() != 6 || &rest[3..4] != ":" || offset_hour > 23 || offset_minute > 59 {
+            return false;
+        }
+        sign * (offset_hour * 60 + offset_minute) as i32
+    };
+
+    if hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+
+    // a leap second is the last second of the day in utc
+    if second == 60 {
+        let utc_minutes = (hour * 60 + minute) as i32 - offset_minutes;
+        return utc_minutes.rem_euclid(24 * 60) == 23 * 60 + 59;
+    }
+
+    true
+}
+
+fn is_date_time(value: &str) -> bool {
+    match value.get(10..11) {
+        Some("T" | "t") => is_date(&value[..10]) && is_time(&value[11..]),
+        _ => false,
+    }
+}
+
+// duration of rfc 3339 appendix A, weeks can not be combined with other units
+fn is_duration(value: &str) -> bool {
+    let Some(rest) = value.strip_prefix('P') else {
+        return false;
+    };
+    let (date, time) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+
+    fn has_units(value: &str, units: &str) -> bool {
+        let mut units = units.chars();
+        let mut digits = 0;
+        for character in value.chars() {
+            if character.is_ascii_digit() {
+                digits += 1;
+                continue;
+            }
+            if digits == 0 || !units.any(|unit| unit == character) {
+                return false;
+            }
+            digits = 0;
+        }
+        digits == 0
+    }
+
+    match time {
+        _ if date.ends_with('W') => time.is_none() && has_units(date, "W"),
+        Some(time) => !time.is_empty() && has_units(date, "YMD") && has_units(time, "HMS"),
+        None => !date.is_empty() && has_units(date, "YMD"),
+    }
+}
+
+fn is_email(value: &str, allow_unicode: bool) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+
+    let is_local_character = |character: char| {
+        character.is_ascii_alphanumeric()
+            || "!#$%&'*+-/=?^_`{|}~.".contains(character)
+            || (allow_unicode && !character.is_ascii() && !character.is_whitespace())
+    };
+    if local.is_empty()
+        || local.starts_with('.')
+        || local.ends_with('.')
+        || local.contains("..")
+        || !local.chars().all(is_local_character)
+    {
+        return false;
+    }
+
+    // an ip address literal, or a host name
+    match domain
+        .strip_prefix('[')
+        .and_then(|domain| domain.strip_suffix(']'))
+    {
+        Some(literal) => match literal.strip_prefix("IPv6:") {
+            Some(address) => address.parse::<Ipv6Addr>().is_ok(),
+            None => literal.parse::<Ipv4Addr>().is_ok(),
+        },
+        None => is_hostname(domain, allow_unicode),
+    }
+}
+
+fn is_hostname(value: &str, allow_unicode: bool) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    if value.is_empty() || value.len() > 253 {
+        return false;
+    }
+
+    value.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|character| {
+                character.is_ascii_alphanumeric()
+                    || character == '-'
+                    || (allow_unicode && character.is_alphanumeric())
+            })
+    })
+}
+
+// the url parser accepts characters that a uri may not have
+fn has_uri_characters(value: &str) -> bool {
+    !value
+        .chars()
+        .any(|character| character.is_whitespace() || "<>\"{}|\\^`".contains(character))
+}
+
+fn is_uri(value: &str) -> bool {
+    has_uri_characters(value) && Url::parse(value).is_ok()
+}
+
+fn is_uri_reference(value: &str) -> bool {
+    let base = Url::parse("https://example.com/").unwrap();
+    has_uri_characters(value) && base.join(value).is_ok()
+}
+
+// the expressions of a uri template are between braces, and not nested
+fn is_uri_template(value: &str) -> bool {
+    let mut in_expression = false;
+    for character in value.chars() {
+        match character {
+            '{' if in_expression => return false,
+            '}' if !in_expression => return false,
+            '{' | '}' => in_expression = !in_expression,
+            _ if character.is_whitespace() => return false,
+            _ => {}
+        }
+    }
+    !in_expression
+}
+
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<_> = value.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, length)| {
+            group.len() == length && group.bytes().all(|byte| byte.is_ascii_hexdigit())
+        })
+}
+
+fn is_json_pointer(value: &str) -> bool {
+    if !value.is_empty() && !value.starts_with('/') {
+        return false;
+    }
+
+    // a tilde is the start of an escape, ~0 or ~1
+    let mut characters = value.chars();
+    while let Some(character) = characters.next() {
+        if character == '~' && !matches!(characters.next(), Some('0' | '1')) {
+            return false;
+        }
+    }
+    true
+}
+
+fn is_relative_json_pointer(value: &str) -> bool {
+    let length = value
+        .bytes()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let (prefix, rest) = value.split_at(length);
+    if prefix.is_empty() || (prefix.len() > 1 && prefix.starts_with('0')) {
+        return false;
+    }
+
+    rest == "#" || is_json_pointer(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_formats() {
+        let check = |format: &str, value: &str| {
+            is_format(format, &ValueRc::String(value.to_owned())).unwrap()
+        };
+
+        assert!(check("date-time", "2024-02-29T23:59:60.5Z"));
+        assert!(!check("date-time", "2023-02-29T12:00:00Z"));
+        assert!(!check("time", "12:00:60+00:00"));
+        assert!(check("duration", "P1Y2MT3S"));
+        assert!(!check("duration", "PT"));
+        assert!(check("email", "a.b@example.com"));
+        assert!(!check("email", "a..b@example.com"));
+        assert!(!check("hostname", "-example.com"));
+        assert!(!check("ipv4", "256.0.0.1"));
+        assert!(check("ipv6", "::1"));
+        assert!(!check("uri", "example"));
+        assert!(check("uri-reference", "../a#b"));
+        assert!(check("uuid", "2eb8aa08-aa98-11ea-b4aa-73b441d16380"));
+        assert!(!check("regex", "^("));
+        assert!(!check("json-pointer", "/a~2"));
+        assert!(check("relative-json-pointer", "1#"));
+
+        assert_eq!(is_format("email", &ValueRc::Null), Some(true));
+        assert_eq!(is_format("unknown", &ValueRc::Null), None);
+    }
+}
@@ -0,0 +1,1609 @@
+use super::formats::is_format;
+use super::output::{OutputUnit, ValidationError};
+use crate::error::Error;
+use crate::schemas::{
+    intermediate::{IntermediateNode, IntermediateType},
+    manager::Manager,
+    meta::MetaSchemaId,
+};
+use crate::utils::json_pointer::{
+    join_json_pointer, json_pointer_from_fragment, json_pointer_to_fragment,
+};
+use crate::utils::value_rc::{
+    as_exact_integer, compare_numbers, format_value, json_equals, ValueRc,
+};
+use indexmap::IndexMap;
+use regex::Regex;
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+use url::Url;
+
+/// Validates instances against the schema nodes loaded in a manager.
+pub struct InstanceValidator<'a, 'b> {
+    manager: &'b Manager<'a>,
+    nodes: HashMap<Url, Rc<IntermediateNode>>,
+    // None for patterns that are not valid regular expressions
+    patterns: HashMap<String, Option<Regex>>,
+    // the nodes that are being validated, with the instance location, a
+    // reference back to one of these would never end
+    stack: Vec<(Url, String)>,
+}
+
+impl<'a, 'b> InstanceValidator<'a, 'b> {
+    /// Fails when the schema has a reference that does not resolve, an
+    /// instance can not be validated against it.
+    pub fn new(manager: &'b Manager<'a>) -> Result<Self, Error> {
+        Ok(Self {
+            manager,
+            nodes: manager
+                .get_intermediate_nodes()?
+                .into_iter()
+                .map(|(node_url, node)| (node_url, Rc::new(node)))
+                .collect(),
+            patterns: HashMap::new(),
+            stack: Vec::new(),
+        })
+    }
+
+    /// Every error in the instance, none if it is valid. Fails when the node
+    /// is not loaded.
+    pub fn validate(
+        &mut self,
+        node_url: &Url,
+        instance: &ValueRc,
+    ) -> Result<Vec<ValidationError>, Error> {
+        Ok(self.evaluate(node_url, instance)?.get_errors())
+    }
+
+    /// The result of every keyword of the schema that applies to the
+    /// instance, with the errors and annotations they produce. Fails when the
+    /// node is not loaded.
+    pub fn evaluate(&mut self, node_url: &Url, instance: &ValueRc) -> Result<OutputUnit, Error> {
+        if !self.nodes.contains_key(node_url) {
+            return Err(Error::RootNodeNotFound {
+                node_url: node_url.clone(),
+            });
+        }
+
+        Ok(self.evaluate_node(node_url, instance, "", ""))
+    }
+
+    fn evaluate_node(
+        &mut self,
+        node_url: &Url,
+        instance: &ValueRc,
+        instance_location: &str,
+        keyword_location: &str,
+    ) -> OutputUnit {
+        let mut unit = OutputUnit {
+            valid: true,
+            keyword_location: keyword_location.to_owned(),
+            absolute_keyword_location: node_url.clone(),
+            instance_location: instance_location.to_owned(),
+            error: None,
+            annotation: None,
+            units: Vec::new(),
+        };
+
+        let frame = (node_url.clone(), instance_location.to_owned());
+        if self.stack.contains(&frame) {
+            return unit;
+        }
+
+        // every reference resolves to a loaded node, so this is not expected,
+        // but an instance does not pass a schema that is not there
+        let Some(node) = self.nodes.get(node_url).cloned() else {
+            unit.valid = false;
+            unit.error = Some(format!("{} is not loaded", node_url));
+            return unit;
+        };
+
+        if let Some(value) = node.boolean {
+            if !value {
+                unit.valid = false;
+                unit.error = Some("no value is allowed here".to_owned());
+            }
+            return unit;
+        }
+
+        self.stack.push(frame);
+
+        let mut context = Context {
+            node: &node,
+            instance_location,
+            keyword_location,
+            units: Vec::new(),
+        };
+
+        self.validate_references(&mut context, instance);
+
+        self.validate_type(&mut context, instance);
+        self.validate_enum(&mut context, instance);
+
+        match instance {
+            ValueRc::String(value) => self.validate_string(&mut context, value),
+            ValueRc::Array(value) => self.validate_array(&mut context, value),
+            ValueRc::Object(value) => self.validate_object(&mut context, value),
+            ValueRc::Null | ValueRc::Bool(_) => {}
+            _ => self.validate_number(&mut context, instance),
+        }
+        self.validate_format(&mut context, instance);
+
+        self.validate_applicators(&mut context, instance);
+        // after every other keyword, they apply to what those did not evaluate
+        self.validate_unevaluated(&mut context, instance);
+        self.annotate(&mut context);
+
+        self.stack.pop();
+
+        unit.valid = context.units.iter().all(|unit| unit.valid);
+        unit.units = context.units;
+        unit
+    }
+
+    // evaluates a sub node of a keyword, the tokens are the path from the
+    // node to the sub node
+    fn evaluate_sub_node(
+        &mut self,
+        context: &Context,
+        tokens: &[&str],
+        sub_node_url: &Url,
+        instance: &ValueRc,
+    ) -> OutputUnit {
+        self.evaluate_node(
+            sub_node_url,
+            instance,
+            context.instance_location,
+            &join_json_pointer(context.keyword_location, tokens),
+        )
+    }
+
+    fn validate_references(&mut self, context: &mut Context, instance: &ValueRc) {
+        if let Some(node_ref_url) = &context.node.reference {
+            let unit = self.evaluate_sub_node(context, &["$ref"], node_ref_url, instance);
+            context.push_unit("$ref", unit.valid, None, None, vec![unit]);
+        }
+
+        if let Some(node_ref_url) = &context.node.dynamic_reference {
+            let keyword = match self.manager.get_node_meta_schema_id(&context.node.node_url) {
+                Some(MetaSchemaId::Draft201909) => "$recursiveRef",
+                _ => "$dynamicRef",
+            };
+
+            // the target depends on the path that led here, not only on
+            // the node
+            let node_ref_url = self
+                .manager
+                .select_node_dynamic_ref_url(&context.node.node_url, &self.get_dynamic_scope())
+                .unwrap_or_else(|| node_ref_url.clone());
+            let unit = self.evaluate_sub_node(context, &[keyword], &node_ref_url, instance);
+            context.push_unit(keyword, unit.valid, None, None, vec![unit]);
+        }
+    }
+
+    // the resources of the nodes that are being validated, outermost first
+    fn get_dynamic_scope(&self) -> Vec<Url> {
+        let mut result: Vec<Url> = Vec::new();
+
+        for (node_url, _instance_location) in &self.stack {
+            let mut resource_url = node_url.clone();
+            resource_url.set_fragment(None);
+
+            if result.last() != Some(&resource_url) {
+                result.push(resource_url);
+            }
+        }
+
+        result
+    }
+
+    fn validate_type(&mut self, context: &mut Context, instance: &ValueRc) {
+        let Some(types) = &context.node.types else {
+            return;
+        };
+
+        let error = (!types
+            .iter()
+            .any(|value| is_instance_of_type(instance, *value)))
+        .then(|| {
+            format!(
+                "expected type {}, got {}",
+                types
+                    .iter()
+                    .map(|value| value.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+                get_instance_type(instance)
+            )
+        });
+
+        context.push_assertion("type", error);
+    }
+
+    fn validate_enum(&mut self, context: &mut Context, instance: &ValueRc) {
+        if let Some(values) = &context.node.enum_values {
+            let error = (!values.iter().any(|value| json_equals(value, instance)))
+                .then(|| "expected one of the enumerated values".to_owned());
+            context.push_assertion("enum", error);
+        }
+
+        if let Some(value) = &context.node.const_value {
+            let error = (!json_equals(value, instance))
+                .then(|| format!("expected {}", format_value(value)));
+            context.push_assertion("const", error);
+        }
+    }
+
+    fn validate_number(&mut self, context: &mut Context, instance: &ValueRc) {
+        let constraints = &context.node.constraints;
+
+        if let Some(value) = &constraints.multiple_of {
+            if let Some(is_multiple) = is_multiple_of(instance, value) {
+                let error = (!is_multiple)
+                    .then(|| format!("expected a multiple of {}", format_value(value)));
+                context.push_assertion("multipleOf", error);
+            }
+        }
+
+        let bounds = [
+            (
+                "minimum",
+                &constraints.minimum,
+                [Ordering::Greater, Ordering::Equal],
+                "at least",
+            ),
+            (
+                "exclusiveMinimum",
+                &constraints.exclusive_minimum,
+                [Ordering::Greater, Ordering::Greater],
+                "greater than",
+            ),
+            (
+                "maximum",
+                &constraints.maximum,
+                [Ordering::Less, Ordering::Equal],
+                "at most",
+            ),
+            (
+                "exclusiveMaximum",
+                &constraints.exclusive_maximum,
+                [Ordering::Less, Ordering::Less],
+                "less than",
+            ),
+        ];
+
+        for (keyword, value, orderings, description) in bounds {
+            let Some(value) = value else {
+                continue;
+            };
+            let Some(ordering) = compare_numbers(instance, value) else {
+                continue;
+            };
+
+            let error = (!orderings.contains(&ordering))
+                .then(|| format!("expected a number {} {}", description, format_value(value)));
+            context.push_assertion(keyword, error);
+        }
+    }
+
+    fn validate_string(&mut self, context: &mut Context, instance: &str) {
+        let constraints = &context.node.constraints;
+        let length = instance.chars().count() as u64;
+
+        if let Some(value) = constraints.min_length {
+            let error = (length < value).then(|| format!("expected at least {} characters", value));
+            context.push_assertion("minLength", error);
+        }
+
+        if let Some(value) = constraints.max_length {
+            let error = (length > value).then(|| format!("expected at most {} characters", value));
+            context.push_assertion("maxLength", error);
+        }
+
+        if let Some(pattern) = &constraints.pattern {
+            let error = match self.get_regex(pattern) {
+                Some(regex) if regex.is_match(instance) => None,
+                Some(_) => Some(format!("expected a string matching {}", pattern)),
+                None => Some(format!("{} is not a supported regular expression", pattern)),
+            };
+            context.push_assertion("pattern", error);
+        }
+    }
+
+    fn validate_format(&mut self, context: &mut Context, instance: &ValueRc) {
+        let Some(format) = &context.node.constraints.format else {
+            return;
+        };
+
+        // an unknown format fails, it would pass anything otherwise
+        let error = match is_format(format, instance) {
+            Some(true) => None,
+            Some(false) => Some(format!("expected a string in the {} format", format)),
+            None => Some(format!("{} is not a supported format", format)),
+        };
+        context.push_assertion("format", error);
+    }
+
+    fn validate_array(&mut self, context: &mut Context, instance: &[Rc<ValueRc>]) {
+        let node = context.node;
+        let prefix_items_urls = &node.prefix_items;
+
+        // before 2020-12 the prefix items were an array in items, followed by
+        // additionalItems
+        let (prefix_items_keyword, items_keyword) =
+            match self.manager.get_node_meta_schema_id(&node.node_url) {
+                Some(MetaSchemaId::Draft202012) => ("prefixItems", "items"),
+                _ if prefix_items_urls.is_empty() => ("items", "items"),
+                _ => ("items", "additionalItems"),
+            };
+
+        if !prefix_items_urls.is_empty() {
+            let units: Vec<_> = prefix_items_urls
+                .iter()
+                .zip(instance)
+                .enumerate()
+                .map(|(index, (item_url, item))| {
+                    let index = index.to_string();
+                    let tokens = [prefix_items_keyword, index.as_str()];
+                    self.evaluate_item(context, &tokens, &index, item_url, item)
+                })
+                .collect();
+
+            // the largest index the prefix items applied to, or true when they
+            // applied to every item
+            let annotation = match units.len() {
+                0 => None,
+                count if count == instance.len() => Some(ValueRc::Bool(true)),
+                count => Some(ValueRc::Integer(count as i64 - 1)),
+            };
+            context.push_unit(
+                prefix_items_keyword,
+                units.iter().all(|unit| unit.valid),
+                None,
+                annotation,
+                units,
+            );
+        }
+
+        if let Some(items_url) = &node.items {
+            let units: Vec<_> = instance
+                .iter()
+                .enumerate()
+                .skip(prefix_items_urls.len())
+                .map(|(index, item)| {
+                    let index = index.to_string();
+                    self.evaluate_item(context, &[items_keyword], &index, items_url, item)
+                })
+                .collect();
+
+            let annotation = (!units.is_empty()).then_some(ValueRc::Bool(true));
+            context.push_unit(
+                items_keyword,
+                units.iter().all(|unit| unit.valid),
+                None,
+                annotation,
+                units,
+            );
+        }
+
+        if let Some(contains_url) = &node.contains {
+            let units: Vec<_> = instance
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let index = index.to_string();
+                    self.evaluate_item(context, &["contains"], &index, contains_url, item)
+                })
+                .collect();
+
+            // the indices of the items that match
+            let indices: Vec<_> = units
+                .iter()
+                .enumerate()
+                .filter(|(_index, unit)| unit.valid)
+                .map(|(index, _unit)| Rc::new(ValueRc::UnsignedInteger(index as u64)))
+                .collect();
+            let count = indices.len() as u64;
+
+            let min_contains = node.constraints.min_contains.unwrap_or(1);
+            let error = (count < min_contains).then(|| {
+                format!(
+                    "expected at least {} items matching the schema, got {}",
+                    min_contains, count
+                )
+            });
+            context.push_unit(
+                "contains",
+                error.is_none(),
+                error,
+                Some(ValueRc::Array(indices)),
+                units,
+            );
+
+            if let Some(value) = node.constraints.max_contains {
+                let error = (count > value).then(|| {
+                    format!(
+                        "expected at most {} items matching the contains schema, got {}",
+                        value, count
+                    )
+                });
+                context.push_assertion("maxContains", error);
+            }
+        }
+
+        let length = instance.len() as u64;
+
+        if let Some(value) = node.constraints.min_items {
+            let error = (length < value).then(|| format!("expected at least {} items", value));
+            context.push_assertion("minItems", error);
+        }
+
+        if let Some(value) = node.constraints.max_items {
+            let error = (length > value).then(|| format!("expected at most {} items", value));
+            context.push_assertion("maxItems", error);
+        }
+
+        if node.constraints.unique_items {
+            let duplicate = instance.iter().enumerate().find_map(|(index, item)| {
+                instance[..index]
+                    .iter()
+                    .position(|other_item| json_equals(item, other_item))
+                    .map(|other_index| (other_index, index))
+            });
+
+            let error = duplicate.map(|(other_index, index)| {
+                format!(
+                    "expected unique items, items {} and {} are equal",
+                    other_index, index
+                )
+            });
+            context.push_assertion("uniqueItems", error);
+        }
+    }
+
+    fn evaluate_item(
+        &mut self,
+        context: &Context,
+        tokens: &[&str],
+        index: &str,
+        item_url: &Url,
+        item: &ValueRc,
+    ) -> OutputUnit {
+        self.evaluate_node(
+            item_url,
+            item,
+            &join_json_pointer(context.instance_location, &[index]),
+            &join_json_pointer(context.keyword_location, tokens),
+        )
+    }
+
+    fn validate_object(&mut self, context: &mut Context, instance: &IndexMap<String, Rc<ValueRc>>) {
+        let node = context.node;
+        let mut evaluated_names = Vec::new();
+
+        if !node.properties.is_empty() {
+            let mut names = Vec::new();
+            let mut units = Vec::new();
+
+            for (name, property_url) in &node.properties {
+                let Some(value) = instance.get(name) else {
+                    continue;
+                };
+
+                units.push(self.evaluate_property(
+                    context,
+                    &["properties", name],
+                    name,
+                    property_url,
+                    value,
+                ));
+                names.push(name.clone());
+            }
+
+            evaluated_names.extend(names.iter().cloned());
+            context.push_unit(
+                "properties",
+                units.iter().all(|unit| unit.valid),
+                None,
+                Some(names_to_value(names)),
+                units,
+            );
+        }
+
+        if !node.pattern_properties.is_empty() {
+            let mut names = Vec::new();
+            let mut units = Vec::new();
+
+            for (pattern, property_url) in &node.pattern_properties {
+                let Some(regex) = self.get_regex(pattern).cloned() else {
+                    continue;
+                };
+
+                for (name, value) in instance {
+                    if !regex.is_match(name) {
+                        continue;
+                    }
+
+                    units.push(self.evaluate_property(
+                        context,
+                        &["patternProperties", pattern],
+                        name,
+                        property_url,
+                        value,
+                    ));
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+
+            evaluated_names.extend(names.iter().cloned());
+            context.push_unit(
+                "patternProperties",
+                units.iter().all(|unit| unit.valid),
+                None,
+                Some(names_to_value(names)),
+                units,
+            );
+        }
+
+        if let Some(additional_properties_url) = &node.additional_properties {
+            let mut names = Vec::new();
+            let mut units = Vec::new();
+
+            for (name, value) in instance {
+                if evaluated_names.contains(name) {
+                    continue;
+                }
+
+                let mut unit = self.evaluate_property(
+                    context,
+                    &["additionalProperties"],
+                    name,
+                    additional_properties_url,
+                    value,
+                );
+
+                // a better message than the one of the boolean schema
+                if self.is_false_node(additional_properties_url) {
+                    unit.error = Some(format!("unexpected property {}", name));
+                }
+
+                units.push(unit);
+                names.push(name.clone());
+            }
+
+            context.push_unit(
+                "additionalProperties",
+                units.iter().all(|unit| unit.valid),
+                None,
+                Some(names_to_value(names)),
+                units,
+            );
+        }
+
+        if let Some(property_names_url) = &node.property_names {
+            let units: Vec<_> = instance
+                .keys()
+                .map(|name| {
+                    self.evaluate_property(
+                        context,
+                        &["propertyNames"],
+                        name,
+                        property_names_url,
+                        &ValueRc::String(name.clone()),
+                    )
+                })
+                .collect();
+
+            context.push_unit(
+                "propertyNames",
+                units.iter().all(|unit| unit.valid),
+                None,
+                None,
+                units,
+            );
+        }
+
+        if !node.required.is_empty() {
+            let missing: Vec<_> = node
+                .required
+                .iter()
+                .filter(|name| !instance.contains_key(*name))
+                .map(|name| name.as_str())
+                .collect();
+
+            let error = match missing.len() {
+                0 => None,
+                1 => Some(format!("missing required property {}", missing[0])),
+                _ => Some(format!(
+                    "missing required properties {}",
+                    missing.join(", ")
+                )),
+            };
+            context.push_assertion("required", error);
+        }
+
+        self.validate_dependencies(context, instance);
+
+        let length = instance.len() as u64;
+
+        if let Some(value) = node.constraints.min_properties {
+            let error = (length < value).then(|| format!("expected at least {} properties", value));
+            context.push_assertion("minProperties", error);
+        }
+
+        if let Some(value) = node.constraints.max_properties {
+            let error = (length > value).then(|| format!("expected at most {} properties", value));
+            context.push_assertion("maxProperties", error);
+        }
+    }
+
+    fn validate_dependencies(
+        &mut self,
+        context: &mut Context,
+        instance: &IndexMap<String, Rc<ValueRc>>,
+    ) {
+        let node = context.node;
+
+        // before 2019-09 both were in dependencies
+        let (required_keyword, schemas_keyword) =
+            match self.manager.get_node_meta_schema_id(&node.node_url) {
+                Some(MetaSchemaId::Draft202012 | MetaSchemaId::Draft201909) => {
+                    ("dependentRequired", "dependentSchemas")
+                }
+                _ => ("dependencies", "dependencies"),
+            };
+
+        if !node.dependent_required.is_empty() {
+            let errors: Vec<_> = node
+                .dependent_required
+                .iter()
+                .filter(|(name, _required)| instance.contains_key(name))
+                .filter_map(|(name, required)| {
+                    let missing: Vec<_> = required
+                        .iter()
+                        .filter(|required_name| !instance.contains_key(*required_name))
+                        .map(|required_name| required_name.as_str())
+                        .collect();
+
+                    (!missing.is_empty()).then(|| {
+                        format!(
+                            "property {} requires the properties {}",
+                            name,
+                            missing.join(", ")
+                        )
+                    })
+                })
+                .collect();
+
+            let error = (!errors.is_empty()).then(|| errors.join(", "));
+            context.push_assertion(required_keyword, error);
+        }
+
+        if !node.dependent_schemas.is_empty() {
+            let instance_value = ValueRc::Object(instance.clone());
+            let units: Vec<_> = node
+                .dependent_schemas
+                .iter()
+                .filter(|(name, _sub_node_url)| instance.contains_key(name))
+                .map(|(name, sub_node_url)| {
+                    self.evaluate_sub_node(
+                        context,
+                        &[schemas_keyword, name],
+                        sub_node_url,
+                        &instance_value,
+                    )
+                })
+                .collect();
+
+            context.push_unit(
+                schemas_keyword,
+                units.iter().all(|unit| unit.valid),
+                None,
+                None,
+                units,
+            );
+        }
+    }
+
+    fn evaluate_property(
+        &mut self,
+        context: &Context,
+        tokens: &[&str],
+        name: &str,
+        property_url: &Url,
+        value: &ValueRc,
+    ) -> OutputUnit {
+        self.evaluate_node(
+            property_url,
+            value,
+            &join_json_pointer(context.instance_location, &[name]),
+            &join_json_pointer(context.keyword_location, tokens),
+        )
+    }
+
+    fn validate_applicators(&mut self, context: &mut Context, instance: &ValueRc) {
+        let node = context.node;
+
+        if !node.all_of.is_empty() {
+            let units = self.evaluate_sub_nodes(context, "allOf", &node.all_of, instance);
+            context.push_unit(
+                "allOf",
+                units.iter().all(|unit| unit.valid),
+                None,
+                None,
+                units,
+            );
+        }
+
+        if !node.any_of.is_empty() {
+            let units = self.evaluate_sub_nodes(context, "anyOf", &node.any_of, instance);
+            let valid = units.iter().any(|unit| unit.valid);
+            let error = (!valid)
+                .then(|| "expected a value matching at least one of the schemas".to_owned());
+            context.push_unit("anyOf", valid, error, None, units);
+        }
+
+        if !node.one_of.is_empty() {
+            let units = self.evaluate_sub_nodes(context, "oneOf", &node.one_of, instance);
+            let count = units.iter().filter(|unit| unit.valid).count();
+            let error = (count != 1).then(|| {
+                format!(
+                    "expected a value matching exactly one of the schemas, it matches {}",
+                    count
+                )
+            });
+            context.push_unit("oneOf", count == 1, error, None, units);
+        }
+
+        // the if schema does not fail the node, it only picks then or else
+        if let Some(if_url) = &node.if_ {
+            let unit = self.evaluate_sub_node(context, &["if"], if_url, instance);
+            let (keyword, sub_node_url) = if unit.valid {
+                ("then", &node.then)
+            } else {
+                ("else", &node.else_)
+            };
+            context.push_unit("if", true, None, None, vec![unit]);
+
+            if let Some(sub_node_url) = sub_node_url {
+                let unit = self.evaluate_sub_node(context, &[keyword], sub_node_url, instance);
+                context.push_unit(keyword, unit.valid, None, None, vec![unit]);
+            }
+        }
+
+        if let Some(sub_node_url) = &node.not {
+            let unit = self.evaluate_sub_node(context, &["not"], sub_node_url, instance);
+            let valid = !unit.valid;
+            let error = (!valid).then(|| "expected a value not matching the schema".to_owned());
+            context.push_unit("not", valid, error, None, vec![unit]);
+        }
+    }
+
+    fn evaluate_sub_nodes(
+        &mut self,
+        context: &Context,
+        keyword: &str,
+        sub_node_urls: &[Url],
+        instance: &ValueRc,
+    ) -> Vec<OutputUnit> {
+        sub_node_urls
+            .iter()
+            .enumerate()
+            .map(|(index, sub_node_url)| {
+                let index = index.to_string();
+                self.evaluate_sub_node(context, &[keyword, &index], sub_node_url, instance)
+            })
+            .collect()
+    }
+
+    fn validate_unevaluated(&mut self, context: &mut Context, instance: &ValueRc) {
+        let node = context.node;
+
+        if let (Some(unevaluated_url), ValueRc::Object(instance)) =
+            (&node.unevaluated_properties, instance)
+        {
+            let mut evaluated = Vec::new();
+            collect_annotations(
+                &context.units,
+                context.instance_location,
+                &[
+                    "properties",
+                    "patternProperties",
+                    "additionalProperties",
+                    "unevaluatedProperties",
+                ],
+                &mut evaluated,
+            );
+            let evaluated_names: Vec<_> = evaluated
+                .iter()
+                .filter_map(|annotation| annotation.as_array())
+                .flatten()
+                .filter_map(|name| name.as_str())
+                .collect();
+
+            let mut names = Vec::new();
+            let mut units = Vec::new();
+
+            for (name, value) in instance {
+                if evaluated_names.contains(&name.as_str()) {
+                    continue;
+                }
+
+                let mut unit = self.evaluate_property(
+                    context,
+                    &["unevaluatedProperties"],
+                    name,
+                    unevaluated_url,
+                    value,
+                );
+
+                if self.is_false_node(unevaluated_url) {
+                    unit.error = Some(format!("unexpected property {}", name));
+                }
+
+                units.push(unit);
+                names.push(name.clone());
+            }
+
+            context.push_unit(
+                "unevaluatedProperties",
+                units.iter().all(|unit| unit.valid),
+                None,
+                Some(names_to_value(names)),
+                units,
+            );
+        }
+
+        if let (Some(unevaluated_url), ValueRc::Array(instance)) =
+            (&node.unevaluated_items, instance)
+        {
+            // the items that contains matched are evaluated since 2020-12
+            let keywords: &[&str] = match self.manager.get_node_meta_schema_id(&node.node_url) {
+                Some(MetaSchemaId::Draft202012) => {
+                    &["prefixItems", "items", "contains", "unevaluatedItems"]
+                }
+                _ => &["items", "additionalItems", "unevaluatedItems"],
+            };
+            let mut evaluated = Vec::new();
+            collect_annotations(
+                &context.units,
+                context.instance_location,
+                keywords,
+                &mut evaluated,
+            );
+
+            let mut is_evaluated = vec![false; instance.len()];
+            for annotation in evaluated {
+                match annotation.as_ref() {
+                    // every item
+                    ValueRc::Bool(true) => is_evaluated.fill(true),
+                    // the items up to an index
+                    ValueRc::Array(indices) => {
+                        for index in indices
+                            .iter()
+                            .filter_map(|index| index.as_unsigned_integer())
+                        {
+                            if let Some(value) = is_evaluated.get_mut(index as usize) {
+                                *value = true;
+                            }
+                        }
+                    }
+                    annotation => {
+                        if let Some(index) = annotation.as_unsigned_integer() {
+                            let end = (index as usize + 1).min(instance.len());
+                            is_evaluated[..end].fill(true);
+                        }
+                    }
+                }
+            }
+
+            let units: Vec<_> = instance
+                .iter()
+                .enumerate()
+                .filter(|(index, _item)| !is_evaluated[*index])
+                .map(|(index, item)| {
+                    let index_string = index.to_string();
+                    let mut unit = self.evaluate_item(
+                        context,
+                        &["unevaluatedItems"],
+                        &index_string,
+                        unevaluated_url,
+                        item,
+                    );
+
+                    if self.is_false_node(unevaluated_url) {
+                        unit.error = Some(format!("unexpected item {}", index));
+                    }
+                    unit
+                })
+                .collect();
+
+            let annotation = (!units.is_empty()).then_some(ValueRc::Bool(true));
+            context.push_unit(
+                "unevaluatedItems",
+                units.iter().all(|unit| unit.valid),
+                None,
+                annotation,
+                units,
+            );
+        }
+    }
+
+    fn annotate(&mut self, context: &mut Context) {
+        if let Some(description) = &context.node.description {
+            context.push_annotation("description", ValueRc::String(description.clone()));
+        }
+
+        if !context.node.examples.is_empty() {
+            context.push_annotation("examples", ValueRc::Array(context.node.examples.clone()));
+        }
+    }
+
+    fn is_false_node(&self, node_url: &Url) -> bool {
+        self.nodes
+            .get(node_url)
+            .is_some_and(|node| node.boolean == Some(false))
+    }
+
+    fn get_regex(&mut self, pattern: &str) -> Option<&Regex> {
+        self.patterns
+            .entry(pattern.to_owned())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .as_ref()
+    }
+}
+
+// what is being evaluated, and the units of its keywords
+struct Context<'c> {
+    node: &'c IntermediateNode,
+    instance_location: &'c str,
+    keyword_location: &'c str,
+    units: Vec<OutputUnit>,
+}
+
+impl<'c> Context<'c> {
+    fn push_unit(
+        &mut self,
+        keyword: &str,
+        valid: bool,
+        error: Option<String>,
+        annotation: Option<ValueRc>,
+        units: Vec<OutputUnit>,
+    ) {
+        let node_url = &self.node.node_url;
+        let pointer = json_pointer_from_fragment(node_url.fragment().unwrap_or_default());
+        let mut keyword_url = node_url.clone();
+        keyword_url.set_fragment(Some(
+            json_pointer_to_fragment(&join_json_pointer(&pointer, &[keyword])).as_str(),
+        ));
+
+        self.units.push(OutputUnit {
+            valid,
+            keyword_location: join_json_pointer(self.keyword_location, &[keyword]),
+            absolute_keyword_location: keyword_url,
+            instance_location: self.instance_location.to_owned(),
+            error,
+            annotation: annotation.map(Rc::new),
+            units,
+        });
+    }
+
+    fn push_assertion(&mut self, keyword: &str, error: Option<String>) {
+        self.push_unit(keyword, error.is_none(), error, None, Vec::new());
+    }
+
+    fn push_annotation(&mut self, keyword: &str, annotation: ValueRc) {
+        self.push_unit(keyword, true, None, Some(annotation), Vec::new());
+    }
+}
+
+// the annotations of the keyword units at the instance location, and of the
+// sub nodes they applied at the same location. A failed sub node drops its
+// annotations when the keyword passed without it, like an alternative of
+// anyOf. Otherwise the node fails anyway, and keeping them avoids reporting
+// every property of a failed $ref as unevaluated.
+fn collect_annotations(
+    units: &[OutputUnit],
+    instance_location: &str,
+    keywords: &[&str],
+    result: &mut Vec<Rc<ValueRc>>,
+) {
+    for unit in units {
+        if unit.instance_location != instance_location {
+            continue;
+        }
+
+        if let Some(annotation) = &unit.annotation {
+            let keyword = unit.keyword_location.rsplit('/').next().unwrap_or_default();
+            if keywords.contains(&keyword) {
+                result.push(annotation.clone());
+            }
+        }
+
+        for sub_unit in &unit.units {
+            if (sub_unit.valid || !unit.valid) && sub_unit.instance_location == instance_location {
+                collect_annotations(&sub_unit.units, instance_location, keywords, result);
+            }
+        }
+    }
+}
+
+fn names_to_value(names: Vec<String>) -> ValueRc {
+    ValueRc::Array(
+        names
+            .into_iter()
+            .map(|name| Rc::new(ValueRc::String(name)))
+            .collect(),
+    )
+}
+
+fn get_instance_type(instance: &ValueRc) -> &'static str {
+    match instance {
+        ValueRc::Null => "null",
+        ValueRc::Bool(_) => "boolean",
+        ValueRc::Integer(_) | ValueRc::UnsignedInteger(_) => "integer",
+        ValueRc::String(_) => "string",
+        ValueRc::Array(_) => "array",
+        ValueRc::Object(_) => "object",
+        _ => "number",
+    }
+}
+
+fn is_instance_of_type(instance: &ValueRc, instance_type: IntermediateType) -> bool {
+    match instance_type {
+        // a number without a fractional part, like 1.0, is an integer too
+        IntermediateType::Integer => instance
+            .as_float()
+            .is_some_and(|value| value.fract() == 0.0),
+        IntermediateType::Number => instance.as_float().is_some(),
+        instance_type => get_instance_type(instance) == instance_type.as_str(),
+    }
+}
+
+fn is_multiple_of(value: &ValueRc, multiple_of: &ValueRc) -> Option<bool> {
+    if let (Some(value), Some(multiple_of)) =
+        (as_exact_integer(value), as_exact_integer(multiple_of))
+    {
+        if multiple_of == 0 {
+            return None;
+        }
+        return Some(value % multiple_of == 0);
+    }
+
+    // allow for the rounding errors of decimal fractions, like 0.3 / 0.1
+    let quotient = value.as_float()? / multiple_of.as_float()?;
+    if !quotient.is_finite() {
+        return None;
+    }
+    Some((quotient - quotient.round()).abs() <= f64::EPSILON * quotient.abs().max(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::meta::MetaSchemaId;
+
+    fn load_schema(manager: &mut Manager, source: &str, meta_schema_id: MetaSchemaId) -> Url {
+        let node: ValueRc = serde_json::from_str(source).unwrap();
+        let node_url: Url = "https://example.com/schema".parse().unwrap();
+
+        manager
+            .load_root_node(Rc::new(node), &node_url, meta_schema_id)
+            .unwrap();
+
+        node_url
+    }
+
+    fn validate(manager: &Manager, node_url: &Url, instance: &str) -> Vec<String> {
+        let instance: ValueRc = serde_json::from_str(instance).unwrap();
+
+        InstanceValidator::new(manager)
+            .unwrap()
+            .validate(node_url, &instance)
+            .unwrap()
+            .into_iter()
+            .map(|error| {
+                format!(
+                    "#{} {}",
+                    error.instance_location,
+                    error.keyword_url.fragment().unwrap_or_default()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validate_reference_siblings() {
+        // before 2019-09 the keywords next to a reference are ignored
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "$ref": "#/definitions/a",
+                "type": "string",
+                "definitions": {
+                    "a": { "type": "integer" }
+                }
+            }
+            "##,
+            MetaSchemaId::Draft07,
+        );
+        assert_eq!(validate(&manager, &node_url, "1"), Vec::<String>::new());
+        assert_eq!(
+            validate(&manager, &node_url, r#""a""#),
+            vec!["# /definitions/a/type".to_owned()]
+        );
+
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "$ref": "#/$defs/a",
+                "type": "string",
+                "$defs": {
+                    "a": { "type": "integer" }
+                }
+            }
+            "##,
+            MetaSchemaId::Draft202012,
+        );
+        assert_eq!(
+            validate(&manager, &node_url, "1"),
+            vec!["# /type".to_owned()]
+        );
+    }
+
+    #[test]
+    fn validate_draft_04_ids() {
+        // an id that is a fragment names a node, any other id is a new base
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "definitions": {
+                    "a": { "id": "#foo", "type": "string" },
+                    "b": { "type": "string" },
+                    "nested": {
+                        "id": "nested",
+                        "definitions": {
+                            "b": { "type": "integer" }
+                        },
+                        "properties": {
+                            "y": { "$ref": "#/definitions/b" }
+                        }
+                    }
+                },
+                "properties": {
+                    "x": { "$ref": "#foo" }
+                },
+                "allOf": [{ "$ref": "nested" }]
+            }
+            "##,
+            MetaSchemaId::Draft04,
+        );
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "x": "str", "y": 1 }"#),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "x": 1, "y": "str" }"#),
+            vec![
+                "#/x /definitions/a/type".to_owned(),
+                "#/y /definitions/b/type".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_broken_schema() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "properties": {
+                    "a": { "$ref": "#/$defs/missing" }
+                }
+            }
+            "##,
+            MetaSchemaId::Draft202012,
+        );
+
+        let error = InstanceValidator::new(&manager).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "unresolved reference {} in {}",
+                node_url.join("#/$defs/missing").unwrap(),
+                node_url.join("#/properties/a").unwrap()
+            )
+        );
+
+        let mut manager = Manager::new();
+        load_schema(&mut manager, "{}", MetaSchemaId::Draft202012);
+        let missing_url: Url = "https://example.com/missing".parse().unwrap();
+        assert!(InstanceValidator::new(&manager)
+            .unwrap()
+            .evaluate(&missing_url, &ValueRc::Null)
+            .is_err());
+    }
+
+    #[test]
+    fn validate_object() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "type": "object",
+                "required": ["id"],
+                "properties": {
+                    "id": { "type": "integer", "minimum": 1 },
+                    "tags": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/tag" },
+                        "uniqueItems": true
+                    }
+                },
+                "patternProperties": { "^x-": true },
+                "additionalProperties": false,
+                "$defs": {
+                    "tag": { "type": "string", "pattern": "^[a-z]+$" }
+                }
+            }
+            "##,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(
+            &manager,
+            &node_url,
+            r#"{ "id": 1.0, "tags": ["a", "b"], "x-a": 1 }"#
+        )
+        .is_empty());
+        assert_eq!(
+            validate(
+                &manager,
+                &node_url,
+                r#"{ "id": 0, "tags": ["a", "A", "a"], "b": 1 }"#
+            ),
+            vec![
+                "#/id /properties/id/minimum",
+                "#/tags/1 /$defs/tag/pattern",
+                "#/tags /properties/tags/uniqueItems",
+                "#/b /additionalProperties",
+            ]
+        );
+        assert_eq!(validate(&manager, &node_url, "[]"), vec!["# /type"]);
+    }
+
+    #[test]
+    fn validate_applicators() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "allOf": [{ "minLength": 2 }],
+                "anyOf": [{ "type": "string" }, { "type": "number" }],
+                "oneOf": [{ "maxLength": 3 }, { "const": "abcd" }],
+                "not": { "enum": ["no"] }
+            }
+            "#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(&manager, &node_url, r#""abc""#).is_empty());
+        assert!(validate(&manager, &node_url, r#""abcd""#).is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#""abcde""#),
+            vec!["# /oneOf", "# /oneOf/0/maxLength", "# /oneOf/1/const"]
+        );
+        assert_eq!(validate(&manager, &node_url, r#""no""#), vec!["# /not"]);
+        assert_eq!(
+            validate(&manager, &node_url, "null"),
+            vec!["# /anyOf", "# /anyOf/0/type", "# /anyOf/1/type"]
+        );
+    }
+
+    #[test]
+    fn validate_draft_04() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "items": [{ "type": "integer", "maximum": 10, "exclusiveMaximum": true }],
+                "additionalItems": false
+            }
+            "#,
+            MetaSchemaId::Draft04,
+        );
+
+        assert!(validate(&manager, &node_url, "[9]").is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, "[10, 1]"),
+            vec!["#/0 /items/0/exclusiveMaximum", "#/1 /additionalItems"]
+        );
+    }
+
+    #[test]
+    fn validate_conditionals() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "if": { "type": "string" },
+                "then": { "minLength": 2 },
+                "else": { "type": "integer" }
+            }
+            "#,
+            MetaSchemaId::Draft07,
+        );
+
+        assert!(validate(&manager, &node_url, r#""ab""#).is_empty());
+        assert!(validate(&manager, &node_url, "1").is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#""a""#),
+            vec!["# /then/minLength"]
+        );
+        assert_eq!(validate(&manager, &node_url, "1.5"), vec!["# /else/type"]);
+    }
+
+    #[test]
+    fn validate_contains() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "contains": { "type": "integer" },
+                "minContains": 2,
+                "maxContains": 3
+            }
+            "#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(&manager, &node_url, r#"[1, "a", 2]"#).is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#"[1, "a"]"#),
+            vec!["# /contains", "#/1 /contains/type"]
+        );
+        assert_eq!(
+            validate(&manager, &node_url, "[1, 2, 3, 4]"),
+            vec!["# /maxContains"]
+        );
+
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"{ "contains": { "const": 1 } }"#,
+            MetaSchemaId::Draft06,
+        );
+        assert_eq!(
+            validate(&manager, &node_url, "[2]"),
+            vec!["# /contains", "#/0 /contains/const"]
+        );
+    }
+
+    #[test]
+    fn validate_property_names() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"{ "propertyNames": { "pattern": "^[a-z]+$" } }"#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(&manager, &node_url, r#"{ "a": 1 }"#).is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "a": 1, "B": 2 }"#),
+            vec!["#/B /propertyNames/pattern"]
+        );
+    }
+
+    #[test]
+    fn validate_dependencies() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "dependentRequired": { "a": ["b"] },
+                "dependentSchemas": { "c": { "required": ["d"] } }
+            }
+            "#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(&manager, &node_url, r#"{ "a": 1, "b": 2, "d": 3 }"#).is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "a": 1 }"#),
+            vec!["# /dependentRequired"]
+        );
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "c": 1 }"#),
+            vec!["# /dependentSchemas/c/required"]
+        );
+
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "dependencies": {
+                    "a": ["b"],
+                    "c": { "required": ["d"] }
+                }
+            }
+            "#,
+            MetaSchemaId::Draft07,
+        );
+
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "a": 1, "c": 2 }"#),
+            vec!["# /dependencies", "# /dependencies/c/required"]
+        );
+    }
+
+    #[test]
+    fn validate_unevaluated() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "type": ["object", "array"],
+                "allOf": [{ "properties": { "a": true } }],
+                "anyOf": [
+                    { "properties": { "b": true }, "required": ["b"] },
+                    { "prefixItems": [true] },
+                    { "properties": { "c": true }, "required": ["d"] }
+                ],
+                "contains": { "const": "x" },
+                "minContains": 0,
+                "unevaluatedProperties": false,
+                "unevaluatedItems": false
+            }
+            "#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(&manager, &node_url, r#"{ "a": 1, "b": 2 }"#).is_empty());
+        assert!(validate(&manager, &node_url, r#"[1, "x", "x"]"#).is_empty());
+        assert_eq!(
+            validate(&manager, &node_url, r#"{ "a": 1, "b": 2, "c": 3 }"#),
+            vec!["#/c /unevaluatedProperties"]
+        );
+        assert_eq!(
+            validate(&manager, &node_url, r#"[1, "x", 2]"#),
+            vec!["#/2 /unevaluatedItems"]
+        );
+
+        // contains does not evaluate items in 2019-09
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "items": [true],
+                "contains": { "const": "x" },
+                "unevaluatedItems": false
+            }
+            "#,
+            MetaSchemaId::Draft201909,
+        );
+        assert_eq!(
+            validate(&manager, &node_url, r#"[1, "x"]"#),
+            vec!["#/1 /unevaluatedItems"]
+        );
+    }
+
+    #[test]
+    fn validate_format() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r#"
+            {
+                "properties": {
+                    "date": { "format": "date" },
+                    "email": { "format": "email" },
+                    "unknown": { "format": "unknown" }
+                }
+            }
+            "#,
+            MetaSchemaId::Draft202012,
+        );
+
+        assert!(validate(
+            &manager,
+            &node_url,
+            r#"{ "date": "2024-02-29", "email": "a@example.com" }"#
+        )
+        .is_empty());
+        assert_eq!(
+            validate(
+                &manager,
+                &node_url,
+                r#"{ "date": "2023-02-29", "email": "a", "unknown": "a" }"#
+            ),
+            vec![
+                "#/date /properties/date/format",
+                "#/email /properties/email/format",
+                "#/unknown /properties/unknown/format",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_dynamic_reference() {
+        let mut manager = Manager::new();
+        let node_url = load_schema(
+            &mut manager,
+            r##"
+            {
+                "properties": {
+                    "loose": { "$ref": "tree" },
+                    "strict": { "$ref": "strict-tree" }
+                },
+                "$defs": {
+                    "tree": {
+                        "$id": "tree",
+                        "$dynamicAnchor": "node",
+                        "properties": {
+                            "data": true,
+                            "children": { "items": { "$dynamicRef": "#node" } }
+                        }
+                    },
+                    "strict-tree": {
+                        "$id": "strict-tree",
+                        "$dynamicAnchor": "node",
+                        "$ref": "tree",
+                        "unevaluatedProperties": false
+                    }
+                }
+            }
+            "##,
+            MetaSchemaId::Draft202012,
+        );
+
+        // both reach the same $dynamicRef, only the strict tree resolves it to
+        // itself
+        assert_eq!(
+            validate(
+                &manager,
+                &node_url,
+                r#"
+                {
+                    "loose": { "children": [{ "daat": 1 }] },
+                    "strict": { "children": [{ "daat": 1 }] }
+                }
+                "#
+            ),
+            vec!["#/strict/children/0/daat /unevaluatedProperties"]
+        );
+    }
+
+    #[test]
+    fn compare_numbers_exactly() {
+        let large: ValueRc = serde_json::from_str("9007199254740993").unwrap();
+        let float: ValueRc = serde_json::from_str("9007199254740992.0").unwrap();
+        let small: ValueRc = serde_json::from_str("9007199254740992").unwrap();
+
+        assert_eq!(compare_numbers(&large, &small), Some(Ordering::Greater));
+        assert!(json_equals(&small, &float));
+        assert_eq!(
+            is_multiple_of(
+                &serde_json::from_str("0.3").unwrap(),
+                &serde_json::from_str("0.1").unwrap()
+            ),
+            Some(true)
+        );
+    }
+}
@@ -0,0 +1,3 @@
+mod formats;
+pub mod instance;
+pub mod output;
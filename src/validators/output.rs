@@ -0,0 +1,295 @@
+use crate::utils::{json_pointer::json_pointer_to_fragment, value_rc::ValueRc};
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::{fmt::Display, rc::Rc};
+use url::Url;
+
+/// The output formats of section 12 of the json schema specification. The
+/// [`InstanceValidator`] and the validate command produce them, generated
+/// packages do not contain validators that could.
+///
+/// [`InstanceValidator`]: super::instance::InstanceValidator
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Only whether the instance is valid
+    Flag,
+    /// A flat list of errors or annotations
+    Basic,
+    /// Errors or annotations, structured like the schema
+    Detailed,
+    /// The result of every keyword, structured like the schema
+    Verbose,
+}
+
+/// The result of evaluating a keyword or a schema against a part of the
+/// instance. The units of a schema are the results of its keywords, the units
+/// of an applicator keyword are the results of its subschemas.
+#[derive(Debug, Clone)]
+pub struct OutputUnit {
+    pub valid: bool,
+    /// Json pointer to the keyword, through the references that were followed.
+    pub keyword_location: String,
+    /// The url of the keyword in the schema.
+    pub absolute_keyword_location: Url,
+    /// Json pointer to the evaluated value in the instance.
+    pub instance_location: String,
+    pub error: Option<String>,
+    pub annotation: Option<Rc<ValueRc>>,
+    pub units: Vec<OutputUnit>,
+}
+
+/// A keyword of the schema that the instance does not satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Json pointer to the invalid value in the instance.
+    pub instance_location: String,
+    /// The url of the keyword in the schema.
+    pub keyword_url: Url,
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{}: {} ({})",
+            json_pointer_to_fragment(&self.instance_location),
+            self.message,
+            self.keyword_url
+        )
+    }
+}
+
+impl OutputUnit {
+    /// Every failed keyword with an error message, outermost first.
+    pub fn get_errors(&self) -> Vec<ValidationError> {
+        self.get_flat_units()
+            .into_iter()
+            .filter_map(|unit| {
+                Some(ValidationError {
+                    instance_location: unit.instance_location.clone(),
+                    keyword_url: unit.absolute_keyword_location.clone(),
+                    message: unit.error.clone()?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_output(&self, format: OutputFormat) -> Value {
+        match format {
+            OutputFormat::Flag => {
+                let mut result = Map::new();
+                result.insert("valid".to_owned(), Value::Bool(self.valid));
+                Value::Object(result)
+            }
+            OutputFormat::Basic => self.to_json(
+                self.get_flat_units()
+                    .into_iter()
+                    .map(|unit| unit.to_json(Vec::new()))
+                    .collect(),
+            ),
+            OutputFormat::Detailed => self.to_json(
+                self.units
+                    .iter()
+                    .filter_map(|unit| unit.to_detailed_json(self.valid))
+                    .collect(),
+            ),
+            OutputFormat::Verbose => self.to_verbose_json(),
+        }
+    }
+
+    // The units with an error when invalid, or with an annotation when
+    // valid. Annotations of failed schemas are dropped, as are the errors of
+    // schemas that did not need to be valid, like the alternatives of anyOf.
+    fn get_flat_units(&self) -> Vec<&OutputUnit> {
+        let mut result = Vec::new();
+        self.collect_flat_units(self.valid, &mut result);
+        result
+    }
+
+    fn collect_flat_units<'u>(&'u self, valid: bool, result: &mut Vec<&'u OutputUnit>) {
+        if self.valid != valid {
+            return;
+        }
+
+        if (!valid && self.error.is_some()) || (valid && self.annotation.is_some()) {
+            result.push(self);
+        }
+
+        for unit in &self.units {
+            unit.collect_flat_units(valid, result);
+        }
+    }
+
+    // Only the units that explain the result are kept, a unit that only
+    // passes on the result of a single unit is replaced by that unit.
+    fn to_detailed_json(&self, valid: bool) -> Option<Value> {
+        if self.valid != valid {
+            return None;
+        }
+
+        let units: Vec<_> = self
+            .units
+            .iter()
+            .filter_map(|unit| unit.to_detailed_json(valid))
+            .collect();
+
+        let explains = if valid {
+            self.annotation.is_some()
+        } else {
+            self.error.is_some()
+        };
+
+        match (explains, units.len()) {
+            (false, 0) => None,
+            (false, 1) => units.into_iter().next(),
+            _ => Some(self.to_json(units)),
+        }
+    }
+
+    fn to_verbose_json(&self) -> Value {
+        self.to_json(
+            self.units
+                .iter()
+                .map(|unit| unit.to_verbose_json())
+                .collect(),
+        )
+    }
+
+    fn to_json(&self, units: Vec<Value>) -> Value {
+        let mut result = Map::new();
+
+        result.insert("valid".to_owned(), Value::Bool(self.valid));
+        result.insert(
+            "keywordLocation".to_owned(),
+            Value::String(self.keyword_location.clone()),
+        );
+        result.insert(
+            "absoluteKeywordLocation".to_owned(),
+            Value::String(self.absolute_keyword_location.to_string()),
+        );
+        result.insert(
+            "instanceLocation".to_owned(),
+            Value::String(self.instance_location.clone()),
+        );
+
+        if let Some(error) = &self.error {
+            result.insert("error".to_owned(), Value::String(error.clone()));
+        }
+
+        // the annotations of a failed schema are dropped
+        if let Some(annotation) = self.annotation.as_ref().filter(|_| self.valid) {
+            result.insert(
+                "annotation".to_owned(),
+                serde_json::to_value(annotation.as_ref()).unwrap_or_default(),
+            );
+        }
+
+        if !units.is_empty() {
+            let name = if self.valid { "annotations" } else { "errors" };
+            result.insert(name.to_owned(), Value::Array(units));
+        }
+
+        Value::Object(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::{manager::Manager, meta::MetaSchemaId};
+    use crate::validators::instance::InstanceValidator;
+    use std::path::Path;
+
+    #[test]
+    fn output_formats() {
+        let mut manager = Manager::new();
+
+        let node: ValueRc = serde_json::from_str(
+            r##"
+            {
+                "properties": {
+                    "tags": { "items": { "$ref": "#/$defs/tag" } }
+                },
+                "$defs": {
+                    "tag": { "type": "string", "description": "A tag" }
+                }
+            }
+            "##,
+        )
+        .unwrap();
+        let node_url: Url = "https://example.com/schema".parse().unwrap();
+        manager
+            .load_root_node(Rc::new(node), &node_url, MetaSchemaId::Draft202012)
+            .unwrap();
+
+        let output_schema_url = Url::from_file_path(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/output-2020-12/schema"),
+        )
+        .unwrap();
+        manager
+            .load_from_url(
+                &output_schema_url,
+                &output_schema_url,
+                MetaSchemaId::Draft202012,
+            )
+            .unwrap();
+        // every output is a flag, the definitions of the other formats are
+        // what they are checked against
+        let mut output_schema_url = manager.resolve_node_url(&output_schema_url).unwrap();
+
+        let invalid: ValueRc = serde_json::from_str(r#"{ "tags": ["a", 1] }"#).unwrap();
+        let valid: ValueRc = serde_json::from_str(r#"{ "tags": ["a"] }"#).unwrap();
+
+        let invalid = InstanceValidator::new(&manager)
+            .unwrap()
+            .evaluate(&node_url, &invalid)
+            .unwrap();
+        let valid = InstanceValidator::new(&manager)
+            .unwrap()
+            .evaluate(&node_url, &valid)
+            .unwrap();
+
+        for (format, fragment) in [
+            (OutputFormat::Flag, "/$defs/flag"),
+            (OutputFormat::Basic, "/$defs/basic"),
+            (OutputFormat::Detailed, "/$defs/detailed"),
+            (OutputFormat::Verbose, "/$defs/verbose"),
+        ] {
+            output_schema_url.set_fragment(Some(fragment));
+
+            for unit in [&invalid, &valid] {
+                let output: ValueRc = serde_json::from_value(unit.to_output(format)).unwrap();
+                assert!(InstanceValidator::new(&manager)
+                    .unwrap()
+                    .validate(&output_schema_url, &output)
+                    .unwrap()
+                    .is_empty());
+            }
+        }
+
+        assert_eq!(
+            invalid.to_output(OutputFormat::Flag),
+            serde_json::json!({ "valid": false })
+        );
+        assert_eq!(
+            invalid.to_output(OutputFormat::Basic)["errors"],
+            serde_json::json!([{
+                "valid": false,
+                "keywordLocation": "/properties/tags/items/$ref/type",
+                "absoluteKeywordLocation": "https://example.com/schema#/$defs/tag/type",
+                "instanceLocation": "/tags/1",
+                "error": "expected type string, got integer"
+            }])
+        );
+        assert_eq!(
+            valid.to_output(OutputFormat::Detailed)["annotations"][0]["annotations"][0]
+                ["annotation"],
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            valid.get_flat_units().last().unwrap().keyword_location,
+            "/properties/tags/items/$ref/description"
+        );
+    }
+}